@@ -0,0 +1,193 @@
+//! Derive macro for the `ScimResource` trait of the `scim-filter` crate.
+//!
+//! The attributes of the resource are the named fields of the struct. They can be configured with:
+//! - `#[scim(rename_all = "camelCase")]` on the struct, to convert all the field names to camel case
+//! - `#[scim(rename = "name")]` on a field, to use a different attribute name
+//! - `#[scim(skip)]` on a field, to hide it from filters
+//!
+//! Every field must implement `scim_filter::AsAttrValue`, which is implemented for the common
+//! scalar types, `Option`, `Vec`, and every type deriving `ScimResource`. On a generic struct the
+//! type parameters used by the fields are bound by `AsAttrValue`.
+
+use proc_macro::TokenStream;
+use proc_macro2::{Ident, TokenStream as TokenStream2, TokenTree};
+use quote::{quote, ToTokens};
+use syn::ext::IdentExt;
+use syn::{
+    parse_macro_input, parse_quote, Data, DeriveInput, Error, Fields, Generics, LitStr, Type,
+    WherePredicate,
+};
+
+#[proc_macro_derive(ScimResource, attributes(scim))]
+pub fn derive_scim_resource(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: DeriveInput) -> Result<TokenStream2, Error> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    &input.ident,
+                    "ScimResource can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "ScimResource can only be derived for structs",
+            ))
+        }
+    };
+
+    let rename_all = container_rename_all(&input)?;
+    let mut field_types = vec![];
+    let mut arms = vec![];
+    for field in fields {
+        let options = FieldOptions::parse(field)?;
+        if options.skip {
+            continue;
+        }
+        field_types.push(&field.ty);
+        let ident = field.ident.as_ref().expect("named fields have an ident");
+        let name = match options.rename {
+            Some(rename) => rename,
+            None if rename_all => to_camel_case(&ident.unraw().to_string()),
+            None => ident.unraw().to_string(),
+        };
+        // the names are compared char by char in lowercase, like the keys of a json resource
+        let lowercase_name: String = name.chars().flat_map(char::to_lowercase).collect();
+        arms.push(quote! {
+            if name == #name
+                || ::core::iter::Iterator::eq(
+                    name.chars().flat_map(::core::primitive::char::to_lowercase),
+                    #lowercase_name.chars(),
+                )
+            {
+                return ::scim_filter::AsAttrValue::as_attr_value(&self.#ident);
+            }
+        });
+    }
+
+    let ident = &input.ident;
+    let generics = add_bounds(input.generics.clone(), &field_types);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::scim_filter::ScimResource for #ident #ty_generics #where_clause {
+            fn get_attr(&self, name: &str) -> ::scim_filter::AttrValue<'_> {
+                #(#arms)*
+                ::scim_filter::AttrValue::Null
+            }
+        }
+
+        impl #impl_generics ::scim_filter::AsAttrValue for #ident #ty_generics #where_clause {
+            fn as_attr_value(&self) -> ::scim_filter::AttrValue<'_> {
+                ::scim_filter::AttrValue::Complex(self)
+            }
+        }
+    })
+}
+
+/// adds a `T: AsAttrValue` bound for every type parameter used by the type of a field, like serde
+/// does, so that a generic struct implements the traits when its type parameters do
+fn add_bounds(mut generics: Generics, field_types: &[&Type]) -> Generics {
+    let used_params: Vec<Ident> = generics
+        .type_params()
+        .map(|param| param.ident.clone())
+        .filter(|param| {
+            field_types
+                .iter()
+                .any(|ty| uses_ident(ty.to_token_stream(), param))
+        })
+        .collect();
+    let where_clause = generics.make_where_clause();
+    for param in used_params {
+        let predicate: WherePredicate = parse_quote!(#param: ::scim_filter::AsAttrValue);
+        where_clause.predicates.push(predicate);
+    }
+    generics
+}
+
+fn uses_ident(tokens: TokenStream2, ident: &Ident) -> bool {
+    tokens.into_iter().any(|token| match token {
+        TokenTree::Ident(token) => &token == ident,
+        TokenTree::Group(group) => uses_ident(group.stream(), ident),
+        _ => false,
+    })
+}
+
+/// reads `#[scim(rename_all = "camelCase")]`, the only supported case convention
+fn container_rename_all(input: &DeriveInput) -> Result<bool, Error> {
+    let mut rename_all = false;
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("scim"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename_all") {
+                let value: LitStr = meta.value()?.parse()?;
+                if value.value() != "camelCase" {
+                    return Err(meta.error("the only supported rename_all value is \"camelCase\""));
+                }
+                rename_all = true;
+                Ok(())
+            } else {
+                Err(meta.error("unsupported scim attribute, expected `rename_all`"))
+            }
+        })?;
+    }
+    Ok(rename_all)
+}
+
+#[derive(Default)]
+struct FieldOptions {
+    rename: Option<String>,
+    skip: bool,
+}
+
+impl FieldOptions {
+    fn parse(field: &syn::Field) -> Result<Self, Error> {
+        let mut options = Self::default();
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("scim"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    let value: LitStr = meta.value()?.parse()?;
+                    options.rename = Some(value.value());
+                    Ok(())
+                } else if meta.path.is_ident("skip") {
+                    options.skip = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("unsupported scim attribute, expected `rename` or `skip`"))
+                }
+            })?;
+        }
+        Ok(options)
+    }
+}
+
+fn to_camel_case(field_name: &str) -> String {
+    let mut camel_case = String::with_capacity(field_name.len());
+    let mut uppercase_next = false;
+    for c in field_name.chars() {
+        if c == '_' {
+            uppercase_next = !camel_case.is_empty();
+        } else if uppercase_next {
+            camel_case.extend(c.to_uppercase());
+            uppercase_next = false;
+        } else {
+            camel_case.push(c);
+        }
+    }
+    camel_case
+}
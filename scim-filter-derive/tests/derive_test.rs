@@ -0,0 +1,142 @@
+use chrono::{DateTime, TimeZone, Utc};
+use scim_filter::{AttrValue, CompiledFilter, ScimResource};
+
+#[derive(ScimResource)]
+#[scim(rename_all = "camelCase")]
+struct User {
+    user_name: String,
+    display_name: Option<String>,
+    active: bool,
+    #[scim(rename = "emails")]
+    email_addresses: Vec<Email>,
+    name: Name,
+    last_login: DateTime<Utc>,
+    #[scim(skip)]
+    #[allow(dead_code)]
+    password: String,
+}
+
+#[derive(ScimResource)]
+struct Email {
+    value: String,
+    r#type: String,
+    primary: bool,
+}
+
+#[derive(ScimResource)]
+#[scim(rename_all = "camelCase")]
+struct Name {
+    family_name: String,
+    given_name: String,
+}
+
+fn user() -> User {
+    User {
+        user_name: "bjensen".to_string(),
+        display_name: None,
+        active: true,
+        email_addresses: vec![
+            Email {
+                value: "bjensen@example.com".to_string(),
+                r#type: "work".to_string(),
+                primary: true,
+            },
+            Email {
+                value: "babs@jensen.org".to_string(),
+                r#type: "home".to_string(),
+                primary: false,
+            },
+        ],
+        name: Name {
+            family_name: "Jensen".to_string(),
+            given_name: "Barbara".to_string(),
+        },
+        last_login: Utc.with_ymd_and_hms(2023, 5, 13, 4, 42, 34).unwrap(),
+        password: "secret".to_string(),
+    }
+}
+
+fn matches(filter: &str) -> bool {
+    let filter: CompiledFilter = filter.parse().unwrap();
+    filter.matches_resource(&user()).unwrap()
+}
+
+#[test]
+fn derived_attributes_are_camel_case() {
+    assert!(matches("userName eq \"bjensen\""));
+    assert!(matches(
+        "name.familyName eq \"Jensen\" and name.givenName sw \"Bar\""
+    ));
+    assert!(!matches("user_name pr"));
+}
+
+#[test]
+fn derived_attributes_are_case_insensitive() {
+    assert!(matches("USERNAME eq \"bjensen\""));
+}
+
+#[derive(ScimResource)]
+struct Localized {
+    #[scim(rename = "Größe")]
+    size: String,
+}
+
+#[test]
+fn derived_non_ascii_attributes_are_case_insensitive_like_json() {
+    let localized = Localized {
+        size: "Mittel".to_string(),
+    };
+    let json = serde_json::json!({"Größe": "Mittel"});
+    for name in ["Größe", "größe", "GRÖßE"] {
+        assert_eq!(json.get_attr(name), localized.get_attr(name));
+        assert_eq!(AttrValue::String("Mittel".into()), localized.get_attr(name));
+    }
+}
+
+#[test]
+fn derived_attributes_can_be_renamed() {
+    assert!(matches("emails.value ew \"jensen.org\""));
+    assert!(matches("emails[type eq \"work\" and primary eq true]"));
+    assert!(!matches("emailAddresses pr"));
+}
+
+#[test]
+fn raw_identifiers_are_unescaped() {
+    assert!(matches("emails.type eq \"home\""));
+}
+
+#[test]
+fn derived_attributes_can_be_skipped() {
+    assert!(!matches("password pr"));
+    assert_eq!(AttrValue::Null, user().get_attr("password"));
+}
+
+#[test]
+fn derived_optional_attributes() {
+    assert!(!matches("displayName pr"));
+    assert!(matches("active eq true and not (displayName pr)"));
+}
+
+#[test]
+fn derived_datetime_attributes() {
+    assert!(matches("lastLogin gt \"2023-01-01T00:00:00Z\""));
+}
+
+#[derive(ScimResource)]
+struct Wrapper<T> {
+    value: T,
+}
+
+#[test]
+fn derived_generic_attributes() {
+    let filter: CompiledFilter = "value eq 42".parse().unwrap();
+    assert!(filter.matches_resource(&Wrapper { value: 42 }).unwrap());
+
+    let filter: CompiledFilter = "value.value ew \"example.com\"".parse().unwrap();
+    let email = Email {
+        value: "bjensen@example.com".to_string(),
+        r#type: "work".to_string(),
+        primary: true,
+    };
+    assert!(filter.matches_resource(&Wrapper { value: email }).unwrap());
+}
@@ -0,0 +1,182 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+
+use crate::error::Error;
+use crate::parser::{scim_filter_parser, Filter};
+use crate::{FilterIter, MatchOptions, Schema, ScimResource};
+
+#[cfg(test)]
+#[path = "test/compiled_filter_test.rs"]
+mod compiled_filter_test;
+
+/// A filter parsed once, that can be matched against any number of resources.
+///
+/// ```
+/// use scim_filter::CompiledFilter;
+///
+/// let filter: CompiledFilter = "userName sw \"j\"".parse().unwrap();
+/// let users = vec![
+///     serde_json::json!({"userName": "jlennon"}),
+///     serde_json::json!({"userName": "pmccartney"}),
+/// ];
+/// assert_eq!(1, filter.filter(users).unwrap().len());
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompiledFilter {
+    filter: Filter<'static>,
+    options: MatchOptions,
+}
+
+impl CompiledFilter {
+    pub fn new(input: &str) -> Result<Self, Error> {
+        Ok(Self::from(scim_filter_parser(input)?))
+    }
+
+    /// sets the schema of the match options, see [`MatchOptions::with_schema`]
+    pub fn with_schema(mut self, schema: Schema) -> Self {
+        self.options = self.options.with_schema(schema);
+        self
+    }
+
+    pub fn schema(&self) -> Option<&Schema> {
+        self.options.schema()
+    }
+
+    /// replaces the options used to match the resources. A schema already set with
+    /// [`CompiledFilter::with_schema`] is kept, unless the options have their own.
+    pub fn with_options(mut self, options: MatchOptions) -> Self {
+        self.options = match self.options.schema() {
+            Some(schema) if options.schema().is_none() => options.with_schema(schema.clone()),
+            _ => options,
+        };
+        self
+    }
+
+    pub fn options(&self) -> &MatchOptions {
+        &self.options
+    }
+
+    /// the parsed filter
+    pub fn filter_expression(&self) -> &Filter<'static> {
+        &self.filter
+    }
+
+    /// checks if a resource matches the filter, the resource is serialized to json first
+    pub fn matches<T>(&self, resource: &T) -> Result<bool, Error>
+    where
+        T: Serialize,
+    {
+        self.matches_value(&serde_json::to_value(resource)?)
+    }
+
+    /// checks if a json value matches the filter
+    pub fn matches_value(&self, resource: &JsonValue) -> Result<bool, Error> {
+        self.matches_resource(resource)
+    }
+
+    /// checks if a resource matches the filter, reading its attributes through [`ScimResource`]
+    pub fn matches_resource<R>(&self, resource: &R) -> Result<bool, Error>
+    where
+        R: ScimResource + ?Sized,
+    {
+        self.filter.r#match(resource, &self.options)
+    }
+
+    /// returns the resources matching the filter, stopping at the first error.
+    ///
+    /// The resources are read through [`ScimResource`], without serializing them.
+    pub fn filter_resources<T>(
+        &self,
+        resources: impl IntoIterator<Item = T>,
+    ) -> Result<Vec<T>, Error>
+    where
+        T: ScimResource,
+    {
+        resources.into_iter().try_fold(vec![], |mut acc, resource| {
+            if self.matches_resource(&resource)? {
+                acc.push(resource);
+            }
+            Ok(acc)
+        })
+    }
+
+    /// returns the resources matching the filter, stopping at the first error.
+    ///
+    /// Each resource is serialized to json to be matched, [`CompiledFilter::filter_resources`] and
+    /// [`CompiledFilter::filter_values`] read the resources without serializing them.
+    pub fn filter<T>(&self, resources: impl IntoIterator<Item = T>) -> Result<Vec<T>, Error>
+    where
+        T: Serialize,
+    {
+        self.filter_iter(resources).collect()
+    }
+
+    /// returns the json values matching the filter, stopping at the first error.
+    ///
+    /// Unlike [`CompiledFilter::filter`] the values are not serialized again, they are matched
+    /// and returned by reference.
+    pub fn filter_values<'v>(
+        &self,
+        resources: impl IntoIterator<Item = &'v JsonValue>,
+    ) -> Result<Vec<&'v JsonValue>, Error> {
+        resources.into_iter().try_fold(vec![], |mut acc, resource| {
+            if self.matches_value(resource)? {
+                acc.push(resource);
+            }
+            Ok(acc)
+        })
+    }
+
+    /// lazily returns the resources matching the filter, see [`FilterIter`]
+    pub fn filter_iter<I>(&self, resources: I) -> FilterIter<'_, I::IntoIter>
+    where
+        I: IntoIterator,
+        I::Item: Serialize,
+    {
+        FilterIter::new(self, resources.into_iter())
+    }
+
+    /// lazily returns the resources matching the filter, reading them through [`ScimResource`]
+    /// without serializing them, see [`FilterIter`]
+    pub fn filter_resources_iter<I>(&self, resources: I) -> FilterIter<'_, I::IntoIter>
+    where
+        I: IntoIterator,
+        I::Item: ScimResource,
+    {
+        FilterIter::from_resources(self, resources.into_iter())
+    }
+}
+
+impl<'a> From<Filter<'a>> for CompiledFilter {
+    fn from(filter: Filter<'a>) -> Self {
+        Self {
+            filter: filter.into_owned(),
+            options: MatchOptions::default(),
+        }
+    }
+}
+
+impl FromStr for CompiledFilter {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s)
+    }
+}
+
+impl TryFrom<&str> for CompiledFilter {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Self::new(value)
+    }
+}
+
+impl Display for CompiledFilter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.filter.fmt(f)
+    }
+}
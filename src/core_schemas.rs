@@ -0,0 +1,255 @@
+//! The schemas defined by RFC 7643 section 8.7, ready to be used with a filter.
+//!
+//! The resource schemas also define the common attributes of RFC 7643 section 3.1, `id`,
+//! `externalId` and `meta`, so that they can be filtered too.
+
+use crate::schema::{AttributeDefinition, AttributeType, Schema};
+
+#[cfg(test)]
+#[path = "test/core_schemas_test.rs"]
+mod core_schemas_test;
+
+impl Schema {
+    pub const USER_URI: &'static str = "urn:ietf:params:scim:schemas:core:2.0:User";
+    pub const GROUP_URI: &'static str = "urn:ietf:params:scim:schemas:core:2.0:Group";
+    pub const ENTERPRISE_USER_URI: &'static str =
+        "urn:ietf:params:scim:schemas:extension:enterprise:2.0:User";
+    pub const SERVICE_PROVIDER_CONFIG_URI: &'static str =
+        "urn:ietf:params:scim:schemas:core:2.0:ServiceProviderConfig";
+
+    /// the `User` resource schema, without extensions.
+    ///
+    /// Users with the enterprise extension are described by
+    /// `Schema::user().with_extension(Schema::enterprise_user())`.
+    pub fn user() -> Self {
+        let mut attributes = common_attributes();
+        attributes.extend([
+            string("userName").required(),
+            complex(
+                "name",
+                vec![
+                    string("formatted"),
+                    string("familyName"),
+                    string("givenName"),
+                    string("middleName"),
+                    string("honorificPrefix"),
+                    string("honorificSuffix"),
+                ],
+            ),
+            string("displayName"),
+            string("nickName"),
+            reference("profileUrl"),
+            string("title"),
+            string("userType"),
+            string("preferredLanguage"),
+            string("locale"),
+            string("timezone"),
+            boolean("active"),
+            string("password"),
+            multi_valued_attribute("emails", string("value"), &["work", "home", "other"]),
+            multi_valued_attribute(
+                "phoneNumbers",
+                string("value"),
+                &["work", "home", "mobile", "fax", "pager", "other"],
+            ),
+            multi_valued_attribute(
+                "ims",
+                string("value"),
+                &["aim", "gtalk", "icq", "xmpp", "msn", "skype", "qq", "yahoo"],
+            ),
+            multi_valued_attribute("photos", reference("value"), &["photo", "thumbnail"]),
+            complex(
+                "addresses",
+                vec![
+                    string("formatted"),
+                    string("streetAddress"),
+                    string("locality"),
+                    string("region"),
+                    string("postalCode"),
+                    string("country"),
+                    string("type").with_canonical_values(&["work", "home", "other"]),
+                    boolean("primary"),
+                ],
+            )
+            .multi_valued(),
+            complex(
+                "groups",
+                vec![
+                    string("value"),
+                    reference("$ref"),
+                    string("display"),
+                    string("type").with_canonical_values(&["direct", "indirect"]),
+                ],
+            )
+            .multi_valued(),
+            multi_valued_attribute("entitlements", string("value"), &[]),
+            multi_valued_attribute("roles", string("value"), &[]),
+            multi_valued_attribute(
+                "x509Certificates",
+                AttributeDefinition::new("value", AttributeType::Binary).case_exact(),
+                &[],
+            ),
+        ]);
+        Self {
+            name: Some("User".to_string()),
+            description: Some("User Account".to_string()),
+            ..Self::new(Self::USER_URI, attributes)
+        }
+    }
+
+    /// the `Group` resource schema
+    pub fn group() -> Self {
+        let mut attributes = common_attributes();
+        attributes.extend([
+            string("displayName").required(),
+            complex(
+                "members",
+                vec![
+                    string("value"),
+                    reference("$ref"),
+                    string("type").with_canonical_values(&["User", "Group"]),
+                ],
+            )
+            .multi_valued(),
+        ]);
+        Self {
+            name: Some("Group".to_string()),
+            description: Some("Group".to_string()),
+            ..Self::new(Self::GROUP_URI, attributes)
+        }
+    }
+
+    /// the enterprise extension of the `User` resource
+    pub fn enterprise_user() -> Self {
+        Self {
+            name: Some("EnterpriseUser".to_string()),
+            description: Some("Enterprise User".to_string()),
+            ..Self::new(
+                Self::ENTERPRISE_USER_URI,
+                vec![
+                    string("employeeNumber"),
+                    string("costCenter"),
+                    string("organization"),
+                    string("division"),
+                    string("department"),
+                    complex(
+                        "manager",
+                        vec![string("value"), reference("$ref"), string("displayName")],
+                    ),
+                ],
+            )
+        }
+    }
+
+    /// the schema of the service provider configuration
+    pub fn service_provider_config() -> Self {
+        let supported = || boolean("supported").required();
+        Self {
+            name: Some("Service Provider Configuration".to_string()),
+            description: Some(
+                "Schema for representing the service provider's configuration".to_string(),
+            ),
+            ..Self::new(
+                Self::SERVICE_PROVIDER_CONFIG_URI,
+                vec![
+                    reference("documentationUri"),
+                    complex("patch", vec![supported()]).required(),
+                    complex(
+                        "bulk",
+                        vec![
+                            supported(),
+                            integer("maxOperations").required(),
+                            integer("maxPayloadSize").required(),
+                        ],
+                    )
+                    .required(),
+                    complex(
+                        "filter",
+                        vec![supported(), integer("maxResults").required()],
+                    )
+                    .required(),
+                    complex("changePassword", vec![supported()]).required(),
+                    complex("sort", vec![supported()]).required(),
+                    complex("etag", vec![supported()]).required(),
+                    complex(
+                        "authenticationSchemes",
+                        vec![
+                            string("type").required(),
+                            string("name").required(),
+                            string("description").required(),
+                            reference("specUri"),
+                            reference("documentationUri"),
+                        ],
+                    )
+                    .multi_valued()
+                    .required(),
+                    meta(),
+                ],
+            )
+        }
+    }
+}
+
+fn common_attributes() -> Vec<AttributeDefinition> {
+    vec![
+        string("id").case_exact(),
+        string("externalId").case_exact(),
+        meta(),
+    ]
+}
+
+fn meta() -> AttributeDefinition {
+    complex(
+        "meta",
+        vec![
+            string("resourceType").case_exact(),
+            date_time("created"),
+            date_time("lastModified"),
+            reference("location").case_exact(),
+            string("version").case_exact(),
+        ],
+    )
+}
+
+/// the multi-valued complex attributes of the user, with a value, a display name, a type and a
+/// primary flag
+fn multi_valued_attribute(
+    name: &str,
+    value: AttributeDefinition,
+    canonical_types: &[&str],
+) -> AttributeDefinition {
+    complex(
+        name,
+        vec![
+            value,
+            string("display"),
+            string("type").with_canonical_values(canonical_types),
+            boolean("primary"),
+        ],
+    )
+    .multi_valued()
+}
+
+fn string(name: &str) -> AttributeDefinition {
+    AttributeDefinition::new(name, AttributeType::String)
+}
+
+fn boolean(name: &str) -> AttributeDefinition {
+    AttributeDefinition::new(name, AttributeType::Boolean)
+}
+
+fn integer(name: &str) -> AttributeDefinition {
+    AttributeDefinition::new(name, AttributeType::Integer)
+}
+
+fn date_time(name: &str) -> AttributeDefinition {
+    AttributeDefinition::new(name, AttributeType::DateTime)
+}
+
+fn reference(name: &str) -> AttributeDefinition {
+    AttributeDefinition::new(name, AttributeType::Reference)
+}
+
+fn complex(name: &str, sub_attributes: Vec<AttributeDefinition>) -> AttributeDefinition {
+    AttributeDefinition::new(name, AttributeType::Complex).with_sub_attributes(sub_attributes)
+}
@@ -3,19 +3,34 @@ use std::fmt::{Display, Formatter};
 use thiserror::Error;
 
 use crate::parser::CompareOp;
+use crate::patch::PatchOperation;
+use crate::schema::AttributeType;
 
 #[derive(Debug, Error)]
 pub enum Error {
     #[error(transparent)]
-    Parser(#[from] nom::error::Error<String>),
+    Parser(#[from] ParseError),
 
     #[error(transparent)]
     SerializationError(#[from] serde_json::Error),
 
-    #[error(
-        "the filter has a wrong format, after parsing the input \"{0}\", the part \"{1}\" remains"
-    )]
-    WrongFilterFormat(String, String),
+    #[error("the number {0} in the filter is out of the range of values that can be represented")]
+    NumberOutOfRange(String),
+
+    #[error(transparent)]
+    Validation(#[from] ValidationError),
+
+    #[error(transparent)]
+    Patch(#[from] PatchError),
+
+    #[error("the sortBy {0} is not an attribute path: {1}")]
+    InvalidSortBy(String, String),
+
+    #[error("the sortOrder {0} is neither ascending nor descending")]
+    InvalidSortOrder(String),
+
+    #[error("The schema definition is invalid: {0}")]
+    InvalidSchema(String),
 
     #[error("The applied filter is invalid")]
     InvalidFilter,
@@ -46,6 +61,10 @@ impl Error {
     pub fn wrong_operator(compare_op: &CompareOp, resource: impl ToString) -> Self {
         Self::WrongOperator(*compare_op, resource.to_string())
     }
+
+    pub fn invalid_schema(error: impl ToString) -> Self {
+        Self::InvalidSchema(error.to_string())
+    }
 }
 
 impl Display for CompareOp {
@@ -64,3 +83,147 @@ impl Display for CompareOp {
         write!(f, "{}", compare_operator_string)
     }
 }
+
+/// A filter that doesn't fit the schema of the resources, see [`crate::parser::Filter::validate`].
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum ValidationError {
+    #[error("the attribute {0} is not defined by the schema")]
+    UnknownAttribute(String),
+
+    #[error("the attribute {0} has no sub-attributes, since it's not complex")]
+    SubAttributeOfSimpleAttribute(String),
+
+    #[error(
+        "the operator {operator} can't be applied to the attribute {attribute} of type {attr_type}"
+    )]
+    UnsupportedOperator {
+        operator: CompareOp,
+        attribute: String,
+        attr_type: AttributeType,
+    },
+
+    #[error(
+        "the value {value} can't be compared with the attribute {attribute} of type {attr_type}"
+    )]
+    WrongValueType {
+        value: String,
+        attribute: String,
+        attr_type: AttributeType,
+    },
+}
+
+/// A PATCH operation that can't be applied to a resource, see [`crate::PatchOp`].
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum PatchError {
+    #[error("the operation {0} is not one of add, remove and replace")]
+    InvalidOperation(String),
+
+    #[error("the path {0} is invalid: {1}")]
+    InvalidPath(String, String),
+
+    #[error("the path {0} doesn't select any value of the resource")]
+    NoTarget(String),
+
+    #[error("the remove operation requires a path")]
+    MissingPath,
+
+    #[error("the {0} operation requires a value")]
+    MissingValue(PatchOperation),
+
+    #[error("the value of the operation is invalid: {0}")]
+    InvalidValue(String),
+}
+
+/// An error in the syntax of a filter, with the position where parsing failed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    input: String,
+    offset: usize,
+    line: usize,
+    column: usize,
+    expected: Vec<&'static str>,
+}
+
+impl ParseError {
+    /// creates an error for the given filter, `remain` is the part of the filter not yet parsed
+    /// when the error happened, so it must be a suffix of `input`.
+    pub fn new(input: &str, remain: &str, expected: Vec<&'static str>) -> Self {
+        let offset = input.len() - remain.len();
+        let before = &input[..offset];
+        let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+        Self {
+            input: input.to_owned(),
+            offset,
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            expected,
+        }
+    }
+
+    /// the whole filter that failed parsing
+    pub fn input(&self) -> &str {
+        &self.input
+    }
+
+    /// byte offset of the error in the filter
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// line of the error, starting from 1
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// column of the error in characters, starting from 1
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// description of the tokens that would have been valid at the error position
+    pub fn expected(&self) -> &[&'static str] {
+        &self.expected
+    }
+
+    /// the token found at the error position, or "end of input"
+    pub fn found(&self) -> String {
+        let remain = &self.input[self.offset..];
+        match remain.split_whitespace().next() {
+            Some(token) if remain.starts_with(token) => format!("\"{}\"", token),
+            _ => match remain.chars().next() {
+                Some(c) => format!("{:?}", c),
+                None => "end of input".to_string(),
+            },
+        }
+    }
+
+    /// renders the line of the filter containing the error, with a caret pointing at the error position
+    ///
+    /// ```text
+    /// a eq "test" and
+    ///                 ^ expected attribute path or opening parenthesis, found end of input at line 1, column 17
+    /// ```
+    pub fn render(&self) -> String {
+        let line = self.input.lines().nth(self.line - 1).unwrap_or_default();
+        format!("{}\n{}^ {}", line, " ".repeat(self.column - 1), self)
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.expected.split_last() {
+            None => write!(f, "unexpected {}", self.found())?,
+            Some((last, [])) => write!(f, "expected {}, found {}", last, self.found())?,
+            Some((last, others)) => write!(
+                f,
+                "expected {} or {}, found {}",
+                others.join(", "),
+                last,
+                self.found()
+            )?,
+        }
+        write!(f, " at line {}, column {}", self.line, self.column)
+    }
+}
+
+impl std::error::Error for ParseError {}
@@ -0,0 +1,114 @@
+use std::iter::FusedIterator;
+
+use serde::Serialize;
+
+use crate::error::Error;
+use crate::{CompiledFilter, ScimResource};
+
+#[cfg(test)]
+#[path = "test/filter_iter_test.rs"]
+mod filter_iter_test;
+
+/// Lazy iterator over the resources matching a [`CompiledFilter`].
+///
+/// Resources that don't match are skipped, and a resource that can't be matched yields an error.
+/// The iterator continues after an error, so the caller decides whether to skip errors
+/// (`.filter_map(Result::ok)`) or to stop at the first one (`.collect::<Result<Vec<_>, _>>()`).
+///
+/// Resources are either serialized to json to be matched, see [`FilterIter::new`], or read
+/// through [`ScimResource`] without being copied, see [`FilterIter::from_resources`].
+#[derive(Clone, Debug)]
+pub struct FilterIter<'f, I: Iterator> {
+    filter: &'f CompiledFilter,
+    resources: I,
+    matches: fn(&CompiledFilter, &I::Item) -> Result<bool, Error>,
+}
+
+impl<'f, I: Iterator> FilterIter<'f, I> {
+    /// matches the resources with [`CompiledFilter::matches`], serializing each of them
+    pub fn new(filter: &'f CompiledFilter, resources: I) -> Self
+    where
+        I::Item: Serialize,
+    {
+        Self {
+            filter,
+            resources,
+            matches: CompiledFilter::matches,
+        }
+    }
+
+    /// matches the resources with [`CompiledFilter::matches_resource`], so that iterating over
+    /// json values, or references to them, doesn't copy them
+    pub fn from_resources(filter: &'f CompiledFilter, resources: I) -> Self
+    where
+        I::Item: ScimResource,
+    {
+        Self {
+            filter,
+            resources,
+            matches: CompiledFilter::matches_resource,
+        }
+    }
+}
+
+impl<'f, I: Iterator> Iterator for FilterIter<'f, I> {
+    type Item = Result<I::Item, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for resource in self.resources.by_ref() {
+            match (self.matches)(self.filter, &resource) {
+                Ok(true) => return Some(Ok(resource)),
+                Ok(false) => continue,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.resources.size_hint().1)
+    }
+}
+
+impl<'f, I: FusedIterator> FusedIterator for FilterIter<'f, I> {}
+
+/// Extension trait to filter any iterator of resources with a [`CompiledFilter`].
+///
+/// ```
+/// use scim_filter::{CompiledFilter, FilterExt};
+///
+/// let filter: CompiledFilter = "active eq true".parse().unwrap();
+/// let users = vec![
+///     serde_json::json!({"userName": "jlennon", "active": true}),
+///     serde_json::json!({"userName": "pmccartney", "active": false}),
+/// ];
+/// let active_users: Vec<_> = users.iter().scim_filter_resources(&filter).filter_map(Result::ok).collect();
+/// assert_eq!(vec![&users[0]], active_users);
+/// ```
+pub trait FilterExt: Iterator + Sized {
+    /// filters serializable resources, see [`FilterIter::new`]
+    fn scim_filter(self, filter: &CompiledFilter) -> FilterIter<'_, Self>
+    where
+        Self::Item: Serialize;
+
+    /// filters resources read through [`ScimResource`], see [`FilterIter::from_resources`]
+    fn scim_filter_resources(self, filter: &CompiledFilter) -> FilterIter<'_, Self>
+    where
+        Self::Item: ScimResource;
+}
+
+impl<I: Iterator> FilterExt for I {
+    fn scim_filter(self, filter: &CompiledFilter) -> FilterIter<'_, Self>
+    where
+        Self::Item: Serialize,
+    {
+        FilterIter::new(filter, self)
+    }
+
+    fn scim_filter_resources(self, filter: &CompiledFilter) -> FilterIter<'_, Self>
+    where
+        Self::Item: ScimResource,
+    {
+        FilterIter::from_resources(filter, self)
+    }
+}
@@ -1,6 +1,30 @@
-pub use error::Error;
+pub use compiled_filter::CompiledFilter;
+pub use error::{Error, ParseError, PatchError, ValidationError};
+pub use filter_iter::{FilterExt, FilterIter};
+pub use list_response::ListResponse;
+pub use match_options::{MatchOptions, MismatchPolicy, MultiValuedNotEqual};
 pub use matcher::scim_filter;
+pub use patch::{PatchOp, PatchOperation};
+pub use query::Query;
+pub use resource::{AsAttrValue, AttrValue, ScimResource};
+pub use schema::{AttributeDefinition, AttributeType, Schema};
+pub use scim_error::{ScimError, ScimType};
+#[cfg(feature = "derive")]
+pub use scim_filter_derive::ScimResource;
+pub use sort::{Sort, SortOrder};
 
+mod compiled_filter;
+mod core_schemas;
 mod error;
+mod filter_iter;
+mod list_response;
+mod match_options;
 mod matcher;
 pub mod parser;
+mod patch;
+mod query;
+mod resource;
+mod schema;
+mod scim_error;
+mod sort;
+mod validator;
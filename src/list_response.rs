@@ -0,0 +1,45 @@
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+
+#[cfg(test)]
+#[path = "test/list_response_test.rs"]
+mod list_response_test;
+
+/// A page of the resources returned by a query, as described by RFC 7644 section 3.4.2.
+///
+/// It serializes to the `urn:ietf:params:scim:api:messages:2.0:ListResponse` json.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ListResponse<T> {
+    /// the number of resources matching the query, in all the pages
+    pub total_results: usize,
+    /// the number of resources in this page
+    pub items_per_page: usize,
+    /// the 1-based index of the first resource of this page
+    pub start_index: usize,
+    pub resources: Vec<T>,
+}
+
+impl<T> ListResponse<T> {
+    pub const SCHEMA_URI: &'static str = "urn:ietf:params:scim:api:messages:2.0:ListResponse";
+
+    pub fn new(resources: Vec<T>, total_results: usize, start_index: usize) -> Self {
+        Self {
+            total_results,
+            items_per_page: resources.len(),
+            start_index,
+            resources,
+        }
+    }
+}
+
+impl<T: Serialize> Serialize for ListResponse<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("ListResponse", 5)?;
+        state.serialize_field("schemas", &[Self::SCHEMA_URI])?;
+        state.serialize_field("totalResults", &self.total_results)?;
+        state.serialize_field("itemsPerPage", &self.items_per_page)?;
+        state.serialize_field("startIndex", &self.start_index)?;
+        state.serialize_field("Resources", &self.resources)?;
+        state.end()
+    }
+}
@@ -0,0 +1,127 @@
+use crate::parser::CompareOp;
+use crate::Schema;
+
+#[cfg(test)]
+#[path = "test/match_options_test.rs"]
+mod match_options_test;
+
+/// What happens when a comparison can't be made, because the value in the filter and the value of
+/// the attribute have different types, or the operator doesn't apply to them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MismatchPolicy {
+    /// the comparison fails with an error, and so does the whole match
+    Error,
+    /// the comparison is a non-match
+    NoMatch,
+}
+
+/// How `ne` applies to a multi-valued attribute.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MultiValuedNotEqual {
+    /// the attribute matches if any of its values is not equal to the value in the filter, like
+    /// the other operators match if any of the values matches. `emails.type ne "work"` matches a
+    /// user with a `work` and a `home` email.
+    #[default]
+    AnyElementDiffers,
+    /// the attribute matches if none of its values is equal to the value in the filter, so
+    /// `emails.type ne "work"` only matches users without a `work` email. An empty list matches,
+    /// and so does a missing or null attribute that the schema declares multi-valued. Single-valued
+    /// attributes are not affected.
+    NoElementEqual,
+}
+
+/// Options for matching a filter against a resource.
+///
+/// The [`Default`] options keep the historical behaviour: a mismatch is a non-match for the `eq`,
+/// `ne`, `co`, `sw` and `ew` operators, and an error for `gt`, `ge`, `lt` and `le`. A multi-valued
+/// attribute matches `ne` when any of its values differs, see [`MultiValuedNotEqual`].
+///
+/// ```
+/// use scim_filter::parser::CompareOp;
+/// use scim_filter::{CompiledFilter, MatchOptions, MismatchPolicy};
+///
+/// let resource = serde_json::json!({"active": true});
+/// let filter = CompiledFilter::new("active gt 1").unwrap();
+/// assert!(filter.matches_value(&resource).is_err());
+///
+/// let filter = filter.with_options(MatchOptions::lenient());
+/// assert!(!filter.matches_value(&resource).unwrap());
+///
+/// let filter = filter.with_options(
+///     MatchOptions::lenient().with_policy(CompareOp::GreaterThan, MismatchPolicy::Error),
+/// );
+/// assert!(filter.matches_value(&resource).is_err());
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct MatchOptions {
+    /// the policy of every operator, in the order of the [`CompareOp`] variants
+    policies: [MismatchPolicy; 9],
+    multi_valued_not_equal: MultiValuedNotEqual,
+    schema: Option<Schema>,
+}
+
+impl MatchOptions {
+    /// every mismatch is an error
+    pub fn strict() -> Self {
+        Self::with_all_policies(MismatchPolicy::Error)
+    }
+
+    /// every mismatch is a non-match
+    pub fn lenient() -> Self {
+        Self::with_all_policies(MismatchPolicy::NoMatch)
+    }
+
+    fn with_all_policies(policy: MismatchPolicy) -> Self {
+        Self {
+            policies: [policy; 9],
+            multi_valued_not_equal: MultiValuedNotEqual::default(),
+            schema: None,
+        }
+    }
+
+    /// sets the policy of a single operator
+    pub fn with_policy(mut self, compare_op: CompareOp, policy: MismatchPolicy) -> Self {
+        self.policies[compare_op as usize] = policy;
+        self
+    }
+
+    pub fn policy(&self, compare_op: CompareOp) -> MismatchPolicy {
+        self.policies[compare_op as usize]
+    }
+
+    pub fn with_multi_valued_not_equal(mut self, semantics: MultiValuedNotEqual) -> Self {
+        self.multi_valued_not_equal = semantics;
+        self
+    }
+
+    pub fn multi_valued_not_equal(&self) -> MultiValuedNotEqual {
+        self.multi_valued_not_equal
+    }
+
+    /// compares the attributes according to the types declared in the schema, instead of guessing
+    /// them from the values: a string is compared as a number or a datetime when the attribute has
+    /// that type, and case-sensitively when the attribute is `caseExact`. See [`Schema`].
+    pub fn with_schema(mut self, schema: Schema) -> Self {
+        self.schema = Some(schema);
+        self
+    }
+
+    pub fn schema(&self) -> Option<&Schema> {
+        self.schema.as_ref()
+    }
+}
+
+impl Default for MatchOptions {
+    fn default() -> Self {
+        [
+            CompareOp::GreaterThan,
+            CompareOp::GreaterThanOrEqual,
+            CompareOp::LessThan,
+            CompareOp::LessThanOrEqual,
+        ]
+        .into_iter()
+        .fold(Self::lenient(), |options, compare_op| {
+            options.with_policy(compare_op, MismatchPolicy::Error)
+        })
+    }
+}
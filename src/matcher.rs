@@ -1,16 +1,20 @@
 use std::convert::identity;
 
+use caseless::default_case_fold_str;
 use chrono::{DateTime, FixedOffset};
-use rust_decimal::prelude::FromPrimitive;
 use rust_decimal::Decimal;
 use serde::Serialize;
-use serde_json::{Map, Number, Value as JsonValue, Value};
+use serde_json::Value as JsonValue;
 
 use crate::error::Error;
 use crate::parser::{
-    scim_filter_parser, AttrExpData, AttrPath, CompValue, CompareOp, Filter, LogExpData, ValFilter,
-    ValuePathData,
+    AttrExpData, AttrPath, CompValue, CompareOp, Filter, LogExpData, ValFilter, ValuePathData,
 };
+use crate::resource::{AttrValue, CaseInsensitiveGet, ScimResource};
+use crate::schema::{
+    find_attribute_definition, find_by_name, AttributeDefinition, AttributeType, Schema,
+};
+use crate::{CompiledFilter, MatchOptions, MismatchPolicy, MultiValuedNotEqual};
 
 #[cfg(test)]
 #[path = "test/matcher_test.rs"]
@@ -22,116 +26,232 @@ pub fn scim_filter<T>(input: &str, resources: impl IntoIterator<Item = T>) -> Re
 where
     T: Serialize,
 {
-    let filter_expression = scim_filter_parser(input)?;
-
-    resources.into_iter().try_fold(vec![], |mut acc, resource| {
-        let resource_value = serde_json::to_value(&resource)?;
-        match filter_expression.r#match(&resource_value) {
-            Ok(true) => {
-                acc.push(resource);
-                Ok(acc)
+    CompiledFilter::new(input)?.filter(resources)
+}
+
+/// The attribute definitions known where an expression is matched.
+#[derive(Clone, Copy)]
+pub(crate) enum Definitions<'s> {
+    /// without a schema, the type of an attribute is guessed from its value
+    Unknown,
+    Schema(&'s Schema),
+    /// the sub-attributes of the attribute of a value path
+    SubAttributes(&'s [AttributeDefinition]),
+}
+
+impl<'s> Definitions<'s> {
+    fn definition(&self, attr_path: &AttrPath) -> Option<&'s AttributeDefinition> {
+        match self {
+            Definitions::Unknown => None,
+            Definitions::Schema(schema) => schema.attribute_definition(attr_path),
+            Definitions::SubAttributes(attributes) => {
+                find_attribute_definition(attributes, attr_path)
             }
-            Ok(false) => Ok(acc),
-            Err(e) => Err(e),
         }
-    })
+    }
+
+    /// whether the attribute, or the attribute of the sub-attribute, is declared multi-valued
+    fn is_multi_valued(&self, attr_path: &AttrPath) -> bool {
+        let attributes = match self {
+            Definitions::Unknown => None,
+            Definitions::Schema(schema) => schema.attributes_of(attr_path.uri().as_deref()),
+            Definitions::SubAttributes(attributes) => Some(*attributes),
+        };
+        attributes
+            .and_then(|attributes| find_by_name(attributes, attr_path.attr_name()))
+            .is_some_and(|definition| definition.multi_valued)
+            || self
+                .definition(attr_path)
+                .is_some_and(|definition| definition.multi_valued)
+    }
+
+    /// the definitions of the attributes inside the value filter of the given attribute
+    fn value_path_definitions(&self, attr_path: &AttrPath) -> Definitions<'s> {
+        match self.definition(attr_path) {
+            Some(definition) if !definition.sub_attributes.is_empty() => {
+                Definitions::SubAttributes(&definition.sub_attributes)
+            }
+            _ => Definitions::Unknown,
+        }
+    }
+}
+
+/// The state threaded through the matching of a filter.
+#[derive(Clone, Copy)]
+pub(crate) struct MatchContext<'s> {
+    definitions: Definitions<'s>,
+    options: &'s MatchOptions,
+}
+
+impl<'s> MatchContext<'s> {
+    pub(crate) fn new(options: &'s MatchOptions) -> Self {
+        Self {
+            definitions: options
+                .schema()
+                .map_or(Definitions::Unknown, Definitions::Schema),
+            options,
+        }
+    }
+
+    /// the context of the value filter of the given attribute
+    fn value_path_context(&self, attr_path: &AttrPath) -> Self {
+        Self {
+            definitions: self.definitions.value_path_definitions(attr_path),
+            options: self.options,
+        }
+    }
 }
 
 impl<'a> Filter<'a> {
-    pub fn r#match(&self, resource: &JsonValue) -> MatcherResult<bool> {
+    pub fn r#match<R: ScimResource + ?Sized>(
+        &self,
+        resource: &R,
+        options: &MatchOptions,
+    ) -> MatcherResult<bool> {
+        self.match_with(resource, MatchContext::new(options))
+    }
+
+    pub(crate) fn match_with<R: ScimResource + ?Sized>(
+        &self,
+        resource: &R,
+        context: MatchContext,
+    ) -> MatcherResult<bool> {
         match self {
-            Filter::AttrExp(attr_expr_data) => attr_expr_data.r#match(resource),
-            Filter::LogExp(log_exp_data) => log_exp_data.r#match(resource),
-            Filter::ValuePath(value_path_data) => value_path_data.r#match(resource),
-            Filter::Sub(is_not, filter) => filter.r#match(resource).map(|filter_result| {
-                if *is_not {
-                    !filter_result
-                } else {
-                    filter_result
-                }
-            }),
+            Filter::AttrExp(attr_expr_data) => attr_expr_data.match_with(resource, context),
+            Filter::LogExp(log_exp_data) => log_exp_data.match_with(resource, context),
+            Filter::ValuePath(value_path_data) => value_path_data.match_with(resource, context),
+            Filter::Sub(is_not, filter) => {
+                filter.match_with(resource, context).map(|filter_result| {
+                    if *is_not {
+                        !filter_result
+                    } else {
+                        filter_result
+                    }
+                })
+            }
         }
     }
 }
 
 impl<'a> AttrExpData<'a> {
-    pub fn r#match(&self, resource: &JsonValue) -> MatcherResult<bool> {
+    pub fn r#match<R: ScimResource + ?Sized>(
+        &self,
+        resource: &R,
+        options: &MatchOptions,
+    ) -> MatcherResult<bool> {
+        self.match_with(resource, MatchContext::new(options))
+    }
+
+    pub(crate) fn match_with<R: ScimResource + ?Sized>(
+        &self,
+        resource: &R,
+        context: MatchContext,
+    ) -> MatcherResult<bool> {
         match self {
             AttrExpData::Present(attr_path) => Ok(!attr_path.extract_value(resource).is_null()),
             AttrExpData::Compare(attr_path, compare_op, comp_value) => {
                 let resource_value = attr_path.extract_value(resource);
-                comp_value.compare_with(compare_op, &resource_value)
+                comp_value.compare_attribute(
+                    compare_op,
+                    &resource_value,
+                    context.definitions.definition(attr_path),
+                    context.definitions.is_multi_valued(attr_path),
+                    context.options,
+                )
             }
         }
     }
 }
 
-trait CaseInsensitiveGet {
-    fn get_insensitive(&self, key: &String) -> Option<&Value>;
-}
+impl AttrPath {
+    /// extracts the value of the attribute from the resource, the value is borrowed from the resource.
+    pub fn extract_value<'r, R: ScimResource + ?Sized>(&self, resource: &'r R) -> AttrValue<'r> {
+        // I do the check in two steps, so first I extract the base resource, that correspond to the attribute name in the expression
+        let base_resource = self.extract_base_value(resource);
+        // extracting the final value based on the presence of a sub_attr
+        match self.sub_attr() {
+            None => base_resource,
+            Some(sub_attr) => base_resource.into_sub_attr(sub_attr),
+        }
+    }
 
-impl CaseInsensitiveGet for Map<String, Value> {
-    fn get_insensitive(&self, key: &String) -> Option<&Value> {
-        for (value_key, value) in self {
-            if value_key.to_lowercase() == key.to_lowercase() {
-                return Some(value);
-            }
+    /// extracts the value of the attribute name, honouring the schema URI prefix.
+    ///
+    /// Attributes of an extension schema are nested in an object keyed by the schema URI, while
+    /// attributes of a core schema are at the top level of the resource.
+    pub(crate) fn extract_base_value<'r, R: ScimResource + ?Sized>(
+        &self,
+        resource: &'r R,
+    ) -> AttrValue<'r> {
+        let Some(uri) = self.uri() else {
+            return resource.get_attr(self.attr_name());
+        };
+        match resource.get_attr(uri) {
+            extension @ AttrValue::Complex(_) => extension.into_sub_attr(self.attr_name()),
+            _ if is_core_schema(uri) => resource.get_attr(self.attr_name()),
+            _ => AttrValue::Null,
         }
-        None
     }
 }
 
 impl AttrPath {
-    pub fn extract_value(&self, resource: &JsonValue) -> JsonValue {
-        let attr_name = self.attr_name().0.to_lowercase();
-        let sub_attr = self.sub_attr().as_ref().map(|sa| sa.0.to_lowercase());
-        // I do the check in two steps, so first I extract the base resource, that correspond to the attribute name in the expression
-        let base_resource = match resource {
-            Value::Null => return JsonValue::Null,
-            Value::Bool(_) => return JsonValue::Null,
-            Value::Number(_) => return JsonValue::Null,
-            Value::String(_) => return JsonValue::Null,
-            Value::Array(array_of_values) => array_of_values
-                .iter()
-                .filter_map(|v| v.get(&attr_name))
-                .cloned()
-                .collect(),
-            Value::Object(object_value) => object_value
-                .get_insensitive(&attr_name)
-                .cloned()
-                .unwrap_or(JsonValue::Null),
+    /// finds the attribute in a json resource, like [`AttrPath::extract_value`], returning its
+    /// JSON pointer and its value
+    pub(crate) fn json_pointer<'v>(
+        &self,
+        resource: &'v JsonValue,
+    ) -> Option<(String, &'v JsonValue)> {
+        let mut pointer = String::new();
+        let base_resource = match self.uri() {
+            None => resource,
+            Some(uri) => match json_child(&mut pointer, resource, uri) {
+                Some(extension @ JsonValue::Object(_)) => extension,
+                _ if is_core_schema(uri) => {
+                    pointer.clear();
+                    resource
+                }
+                _ => return None,
+            },
         };
-        // extracting the final value based on the presence of a sub_attr
-        match (base_resource.clone(), sub_attr) {
-            (JsonValue::Null, None) => JsonValue::Null,
-            (JsonValue::Bool(_), None) => base_resource.clone(),
-            (JsonValue::Number(_), None) => base_resource.clone(),
-            (JsonValue::String(_), None) => base_resource.clone(),
-            (
-                JsonValue::Null | JsonValue::Bool(_) | JsonValue::Number(_) | JsonValue::String(_),
-                Some(_),
-            ) => JsonValue::Null,
-            (JsonValue::Array(_), None) => base_resource.clone(),
-            (JsonValue::Array(array_of_values), Some(sub_attr)) => JsonValue::Array(
-                array_of_values
-                    .iter()
-                    .filter_map(|v| v.get(&sub_attr))
-                    .cloned()
-                    .collect(),
-            ),
-            (JsonValue::Object(_), None) => base_resource.clone(),
-            (JsonValue::Object(object_value), Some(sub_attr)) => object_value
-                .get_insensitive(&sub_attr)
-                .cloned()
-                .unwrap_or(JsonValue::Null),
+        let mut value = json_child(&mut pointer, base_resource, self.attr_name())?;
+        if let Some(sub_attr) = self.sub_attr() {
+            value = json_child(&mut pointer, value, sub_attr)?;
         }
+        Some((pointer, value))
     }
 }
 
+/// reads the attribute of a json object, appending its key to the pointer
+fn json_child<'v>(pointer: &mut String, value: &'v JsonValue, name: &str) -> Option<&'v JsonValue> {
+    let (key, child) = value.as_object()?.get_key_value_insensitive(name)?;
+    pointer.push('/');
+    pointer.push_str(&key.replace('~', "~0").replace('/', "~1"));
+    Some(child)
+}
+
+/// core schemas are the ones defined by RFC 7643, like `urn:ietf:params:scim:schemas:core:2.0:User`
+pub(crate) fn is_core_schema(uri: &str) -> bool {
+    const CORE_SCHEMA_PREFIX: &str = "urn:ietf:params:scim:schemas:core:";
+    uri.get(..CORE_SCHEMA_PREFIX.len())
+        .is_some_and(|prefix| prefix.eq_ignore_ascii_case(CORE_SCHEMA_PREFIX))
+}
+
 impl<'a> LogExpData<'a> {
-    pub fn r#match(&self, resource: &JsonValue) -> MatcherResult<bool> {
+    pub fn r#match<R: ScimResource + ?Sized>(
+        &self,
+        resource: &R,
+        options: &MatchOptions,
+    ) -> MatcherResult<bool> {
+        self.match_with(resource, MatchContext::new(options))
+    }
+
+    pub(crate) fn match_with<R: ScimResource + ?Sized>(
+        &self,
+        resource: &R,
+        context: MatchContext,
+    ) -> MatcherResult<bool> {
         // check if the left side is a match
-        let left_match = self.left.r#match(resource)?;
+        let left_match = self.left.match_with(resource, context)?;
 
         // if it is, and the operator is an or, no need to check for the right side
         if left_match && self.log_exp_operator.is_or() {
@@ -140,7 +260,7 @@ impl<'a> LogExpData<'a> {
             || (!left_match && self.log_exp_operator.is_or())
         {
             // if it's an and operator, or if it's an or and the left don't match, I check for the right side
-            self.right.r#match(resource)
+            self.right.match_with(resource, context)
         } else {
             Ok(false)
         }
@@ -148,34 +268,140 @@ impl<'a> LogExpData<'a> {
 }
 
 impl<'a> ValuePathData<'a> {
-    pub fn r#match(&self, resource: &JsonValue) -> MatcherResult<bool> {
-        self.val_filter().r#match(self.attr_path(), resource)
+    pub fn r#match<R: ScimResource + ?Sized>(
+        &self,
+        resource: &R,
+        options: &MatchOptions,
+    ) -> MatcherResult<bool> {
+        self.match_with(resource, MatchContext::new(options))
+    }
+
+    pub(crate) fn match_with<R: ScimResource + ?Sized>(
+        &self,
+        resource: &R,
+        context: MatchContext,
+    ) -> MatcherResult<bool> {
+        self.val_filter()
+            .match_with(self.attr_path(), resource, context)
+    }
+
+    /// returns the indices of the values of the attribute that match the value filter, like the
+    /// elements addressed by the PATCH path `emails[type eq "work"]`.
+    ///
+    /// A single-valued complex attribute is a single element, with index `0`.
+    ///
+    /// ```
+    /// use scim_filter::parser::{scim_filter_parser, Filter};
+    ///
+    /// let Filter::ValuePath(value_path) = scim_filter_parser("emails[type eq \"work\"]").unwrap()
+    /// else {
+    ///     unreachable!()
+    /// };
+    /// let user = serde_json::json!({"emails": [
+    ///     {"type": "home", "value": "babs@example.org"},
+    ///     {"type": "work", "value": "bjensen@example.com"},
+    /// ]});
+    /// assert_eq!(
+    ///     vec![1],
+    ///     value_path.matching_elements(&user, &Default::default()).unwrap()
+    /// );
+    /// assert_eq!(
+    ///     vec!["/emails/1"],
+    ///     value_path.matching_pointers(&user, &Default::default()).unwrap()
+    /// );
+    /// ```
+    pub fn matching_elements<R: ScimResource + ?Sized>(
+        &self,
+        resource: &R,
+        options: &MatchOptions,
+    ) -> MatcherResult<Vec<usize>> {
+        self.val_filter()
+            .matching_elements(self.attr_path(), resource, MatchContext::new(options))
+    }
+
+    /// returns the JSON pointers, as defined by RFC 6901, of the values of the attribute that
+    /// match the value filter. The keys of the pointers are written as in the resource.
+    ///
+    /// There are no pointers when the attribute is not found in the json, or when it's nested in
+    /// the values of a multi-valued attribute.
+    pub fn matching_pointers(
+        &self,
+        resource: &JsonValue,
+        options: &MatchOptions,
+    ) -> MatcherResult<Vec<String>> {
+        let Some((pointer, value)) = self.attr_path().json_pointer(resource) else {
+            return Ok(vec![]);
+        };
+        let indices = self.matching_elements(resource, options)?;
+        Ok(match value {
+            JsonValue::Array(_) => indices
+                .into_iter()
+                .map(|index| format!("{pointer}/{index}"))
+                .collect(),
+            _ => indices.into_iter().map(|_| pointer.clone()).collect(),
+        })
     }
 }
 
 impl<'a> ValFilter<'a> {
-    pub fn r#match(&self, attr_path: &AttrPath, resource: &JsonValue) -> MatcherResult<bool> {
-        let mut sub_resource = resource[&attr_path.attr_name().0].clone();
-        if let Some(sub_attr) = attr_path.sub_attr() {
-            sub_resource = match sub_resource {
-                Value::Null => return Ok(false),
-                Value::Bool(_) => return Ok(false),
-                Value::Number(_) => return Ok(false),
-                Value::String(_) => return Ok(false),
-                Value::Array(arr_values) => JsonValue::Array(
-                    arr_values
-                        .iter()
-                        .map(|arr_value| arr_value[&sub_attr.0].clone())
-                        .collect(),
-                ),
-                Value::Object(obj_value) => obj_value[&sub_attr.0].clone(),
-            };
-        }
+    pub fn r#match<R: ScimResource + ?Sized>(
+        &self,
+        attr_path: &AttrPath,
+        resource: &R,
+        options: &MatchOptions,
+    ) -> MatcherResult<bool> {
+        self.match_with(attr_path, resource, MatchContext::new(options))
+    }
+
+    pub(crate) fn match_with<R: ScimResource + ?Sized>(
+        &self,
+        attr_path: &AttrPath,
+        resource: &R,
+        context: MatchContext,
+    ) -> MatcherResult<bool> {
+        Ok(!self
+            .matching_elements(attr_path, resource, context)?
+            .is_empty())
+    }
+
+    /// the indices of the values of the attribute that match the filter
+    pub(crate) fn matching_elements<R: ScimResource + ?Sized>(
+        &self,
+        attr_path: &AttrPath,
+        resource: &R,
+        context: MatchContext,
+    ) -> MatcherResult<Vec<usize>> {
+        // the attribute is resolved like in an attribute expression
+        let sub_resource = attr_path.extract_value(resource);
+        let context = context.value_path_context(attr_path);
+        let values = match &sub_resource {
+            // an attribute without values has no value matching the filter
+            AttrValue::Null => return Ok(vec![]),
+            // every condition of the value filter must hold on the same value of the attribute
+            AttrValue::Multi(values) => values.as_slice(),
+            sub_resource => std::slice::from_ref(sub_resource),
+        };
+        values
+            .iter()
+            .enumerate()
+            .try_fold(vec![], |mut indices, (index, value)| {
+                if self.match_sub_resource(value, context)? {
+                    indices.push(index);
+                }
+                Ok(indices)
+            })
+    }
+
+    fn match_sub_resource(
+        &self,
+        sub_resource: &AttrValue,
+        context: MatchContext,
+    ) -> MatcherResult<bool> {
         match self {
-            ValFilter::AttrExp(attr_exp_data) => attr_exp_data.r#match(&sub_resource),
-            ValFilter::LogExp(log_exp_data) => log_exp_data.r#match(&sub_resource),
+            ValFilter::AttrExp(attr_exp_data) => attr_exp_data.match_with(sub_resource, context),
+            ValFilter::LogExp(log_exp_data) => log_exp_data.match_with(sub_resource, context),
             ValFilter::SubFilter(is_not, sub_filter) => sub_filter
-                .r#match(attr_path, &sub_resource)
+                .match_sub_resource(sub_resource, context)
                 .map(|sub_filter_result| {
                     if *is_not {
                         !sub_filter_result
@@ -196,7 +422,7 @@ impl<'a> CompValue<'a> {
         }
     }
 
-    fn compare_null(resource_value: &JsonValue, compare_op: &CompareOp) -> MatcherResult<bool> {
+    fn compare_null(resource_value: &AttrValue, compare_op: &CompareOp) -> MatcherResult<bool> {
         match compare_op {
             CompareOp::Equal => Ok(resource_value.is_null()),
             CompareOp::NotEqual => Ok(!resource_value.is_null()),
@@ -221,7 +447,22 @@ impl<'a> CompValue<'a> {
             .ok_or_else(|| Error::wrong_operator(compare_op, resource_value.to_string()))
     }
 
-    fn compare_string(resource_value: &str, compare_op: &CompareOp, comp_value: &str) -> bool {
+    /// strings are compared case-insensitively, as specified by RFC 7643, unless the attribute is
+    /// case exact. The case-insensitive comparison uses the Unicode case folding.
+    fn compare_string(
+        resource_value: &str,
+        compare_op: &CompareOp,
+        comp_value: &str,
+        case_exact: bool,
+    ) -> bool {
+        if !case_exact {
+            return Self::compare_string(
+                &default_case_fold_str(resource_value),
+                compare_op,
+                &default_case_fold_str(comp_value),
+                true,
+            );
+        }
         match compare_op {
             CompareOp::Equal => resource_value == comp_value,
             CompareOp::NotEqual => resource_value != comp_value,
@@ -248,92 +489,142 @@ impl<'a> CompValue<'a> {
     pub fn compare_with(
         &self,
         compare_op: &CompareOp,
-        resource_value: &JsonValue,
+        resource_value: &AttrValue,
+        options: &MatchOptions,
     ) -> MatcherResult<bool> {
-        match self.do_compare_with(compare_op, resource_value) {
-            Ok(res) => Ok(res),
-            Err(err) => match compare_op {
-                CompareOp::Equal => Ok(false),
-                CompareOp::NotEqual => Ok(false),
-                CompareOp::Contains => Ok(false),
-                CompareOp::StartsWith => Ok(false),
-                CompareOp::EndsWith => Ok(false),
-                CompareOp::GreaterThan => Err(err),
-                CompareOp::GreaterThanOrEqual => Err(err),
-                CompareOp::LessThan => Err(err),
-                CompareOp::LessThanOrEqual => Err(err),
-            },
+        self.compare_attribute(compare_op, resource_value, None, false, options)
+    }
+
+    /// compares the value with the type declared by the attribute definition, instead of guessing it
+    pub fn compare_with_definition(
+        &self,
+        compare_op: &CompareOp,
+        resource_value: &AttrValue,
+        definition: &AttributeDefinition,
+        options: &MatchOptions,
+    ) -> MatcherResult<bool> {
+        self.compare_attribute(
+            compare_op,
+            resource_value,
+            Some(definition),
+            definition.multi_valued,
+            options,
+        )
+    }
+
+    /// compares the value of an attribute, `multi_valued` is whether the schema declares the
+    /// attribute, or the attribute of the sub-attribute, multi-valued
+    pub(crate) fn compare_attribute(
+        &self,
+        compare_op: &CompareOp,
+        resource_value: &AttrValue,
+        definition: Option<&AttributeDefinition>,
+        multi_valued: bool,
+        options: &MatchOptions,
+    ) -> MatcherResult<bool> {
+        Self::apply_mismatch_policy(
+            options.policy(*compare_op),
+            self.compare_multi_valued_not_equal(
+                compare_op,
+                resource_value,
+                multi_valued,
+                options,
+                |compare_op| {
+                    let policy = options.policy(*compare_op);
+                    match definition {
+                        Some(definition) => self.do_compare_with_definition(
+                            compare_op,
+                            resource_value,
+                            definition,
+                            policy,
+                        ),
+                        None => self.do_compare_with(compare_op, resource_value, policy),
+                    }
+                },
+            ),
+        )
+    }
+
+    /// with [`MultiValuedNotEqual::NoElementEqual`] a multi-valued attribute is not equal to the
+    /// value when none of its values is equal to it, which is always the case for an attribute
+    /// declared multi-valued without values
+    fn compare_multi_valued_not_equal(
+        &self,
+        compare_op: &CompareOp,
+        resource_value: &AttrValue,
+        multi_valued: bool,
+        options: &MatchOptions,
+        compare: impl Fn(&CompareOp) -> MatcherResult<bool>,
+    ) -> MatcherResult<bool> {
+        match (compare_op, resource_value, options.multi_valued_not_equal()) {
+            (CompareOp::NotEqual, AttrValue::Multi(_), MultiValuedNotEqual::NoElementEqual) => {
+                compare(&CompareOp::Equal).map(|equal| !equal)
+            }
+            // a missing attribute is still equal to null
+            (CompareOp::NotEqual, AttrValue::Null, MultiValuedNotEqual::NoElementEqual)
+                if multi_valued && *self != CompValue::Null =>
+            {
+                Ok(true)
+            }
+            _ => compare(compare_op),
+        }
+    }
+
+    /// a value that can't be compared is either an error or a non-match, see [`MatchOptions`]
+    fn apply_mismatch_policy(
+        policy: MismatchPolicy,
+        result: MatcherResult<bool>,
+    ) -> MatcherResult<bool> {
+        match (result, policy) {
+            (Ok(res), _) => Ok(res),
+            (Err(_), MismatchPolicy::NoMatch) => Ok(false),
+            (Err(err), MismatchPolicy::Error) => Err(err),
         }
     }
 
+    /// compares the value, guessing its type from the value in the filter. The values of a
+    /// multi-valued attribute that can't be compared are handled according to the policy.
     pub fn do_compare_with(
         &self,
         compare_op: &CompareOp,
-        resource_value: &JsonValue,
+        resource_value: &AttrValue,
+        policy: MismatchPolicy,
     ) -> MatcherResult<bool> {
         match self {
             CompValue::False => match resource_value {
-                Value::Bool(bool_value) => Self::compare_false(*bool_value, compare_op),
-                Value::Array(values) => values
-                    .iter()
-                    .try_fold(vec![], |mut acc, value| {
-                        value
-                            .as_bool()
-                            .ok_or_else(|| Error::MalformedBoolean(value.to_string()))
-                            .and_then(|value| {
-                                Self::compare_false(value, compare_op).map(|v| {
-                                    acc.push(v);
-                                    acc
-                                })
-                            })
+                AttrValue::Bool(bool_value) => Self::compare_false(*bool_value, compare_op),
+                AttrValue::Multi(values) => {
+                    Self::compare_multi_valued(values, policy, |value| match value {
+                        AttrValue::Bool(value) => Self::compare_false(*value, compare_op),
+                        value => Err(Error::MalformedBoolean(value.to_string())),
                     })
-                    .map(|results| results.into_iter().any(identity)),
+                }
                 value => Err(Error::MalformedBoolean(value.to_string())),
             },
             CompValue::Null => Self::compare_null(resource_value, compare_op),
             CompValue::True => match resource_value {
-                Value::Bool(bool_value) => Self::compare_true(*bool_value, compare_op),
-                Value::Array(values) => values
-                    .iter()
-                    .try_fold(vec![], |mut acc, value| {
-                        value
-                            .as_bool()
-                            .ok_or_else(|| Error::MalformedBoolean(value.to_string()))
-                            .and_then(|value| {
-                                Self::compare_true(value, compare_op).map(|v| {
-                                    acc.push(v);
-                                    acc
-                                })
-                            })
+                AttrValue::Bool(bool_value) => Self::compare_true(*bool_value, compare_op),
+                AttrValue::Multi(values) => {
+                    Self::compare_multi_valued(values, policy, |value| match value {
+                        AttrValue::Bool(value) => Self::compare_true(*value, compare_op),
+                        value => Err(Error::MalformedBoolean(value.to_string())),
                     })
-                    .map(|results| results.into_iter().any(identity)),
+                }
                 value => Err(Error::MalformedBoolean(value.to_string())),
             },
             CompValue::Number(comp_value) => match resource_value {
-                JsonValue::Array(values) => values
-                    .iter()
-                    .try_fold(vec![], |mut acc, value| {
-                        value
-                            .as_number()
-                            .ok_or_else(|| Error::MalformedNumber(value.to_string()))
-                            .and_then(|number| {
-                                Self::convert_number_to_decimal(number)
-                                    .ok_or_else(|| Error::MalformedNumber(number.to_string()))
-                            })
-                            .and_then(|value| {
-                                Self::compare_number(&value, compare_op, comp_value).map(|v| {
-                                    acc.push(v);
-                                    acc
-                                })
-                            })
+                AttrValue::Multi(values) => {
+                    Self::compare_multi_valued(values, policy, |value| match value {
+                        AttrValue::Number(value) => {
+                            Self::compare_number(value, compare_op, comp_value)
+                        }
+                        value => Err(Error::MalformedNumber(value.to_string())),
                     })
-                    .map(|results| results.into_iter().any(identity)),
-                JsonValue::Number(number_value) => Self::convert_number_to_decimal(number_value)
-                    .ok_or_else(|| Error::MalformedNumber(number_value.to_string()))
-                    .and_then(|resource_value_as_decimal| {
-                        Self::compare_number(&resource_value_as_decimal, compare_op, comp_value)
-                    }),
-                JsonValue::String(str_value) => Self::convert_str_to_decimal(str_value)
+                }
+                AttrValue::Number(number_value) => {
+                    Self::compare_number(number_value, compare_op, comp_value)
+                }
+                AttrValue::String(str_value) => Self::convert_str_to_decimal(str_value)
                     .ok_or_else(|| Error::MalformedString(str_value.to_string()))
                     .and_then(|resource_value_as_decimal| {
                         Self::compare_number(&resource_value_as_decimal, compare_op, comp_value)
@@ -344,7 +635,7 @@ impl<'a> CompValue<'a> {
                 // attempt to match the string as a datetime
                 if let Some(datetime) = Self::convert_str_to_datetime(comp_value) {
                     return match resource_value {
-                        Value::String(str_value) => {
+                        AttrValue::String(str_value) => {
                             match Self::convert_str_to_datetime(str_value) {
                                 None => Err(Error::MalformedDatetime(str_value.to_string())),
                                 Some(value_datetime) => {
@@ -352,24 +643,23 @@ impl<'a> CompValue<'a> {
                                 }
                             }
                         }
+                        AttrValue::DateTime(value_datetime) => {
+                            Self::compare_datetime(value_datetime, compare_op, &datetime)
+                        }
                         value => Err(Error::MalformedNumber(value.to_string())),
                     };
                 }
 
                 match resource_value {
-                    JsonValue::Array(values) => values
-                        .iter()
-                        .try_fold(vec![], |mut acc, value| {
-                            value
-                                .as_str()
-                                .ok_or_else(|| Error::MalformedString(value.to_string()))
-                                .map(|value| {
-                                    acc.push(Self::compare_string(value, compare_op, comp_value));
-                                    acc
-                                })
+                    AttrValue::Multi(values) => {
+                        Self::compare_multi_valued(values, policy, |value| match value {
+                            AttrValue::String(value) => {
+                                Ok(Self::compare_string(value, compare_op, comp_value, false))
+                            }
+                            value => Err(Error::MalformedString(value.to_string())),
                         })
-                        .map(|results| results.into_iter().any(identity)),
-                    JsonValue::String(val_string) => {
+                    }
+                    AttrValue::String(val_string) => {
                         if Self::convert_str_to_datetime(val_string).is_some() {
                             // the resource value is a date. Since the comparison value is not, this is an error.
                             return Err(Error::MalformedDatetime(comp_value.to_string()));
@@ -378,14 +668,123 @@ impl<'a> CompValue<'a> {
                             // the resource value is a date. Since the comparison value is not, this is an error.
                             return Err(Error::MalformedNumber(comp_value.to_string()));
                         }
-                        Ok(Self::compare_string(val_string, compare_op, comp_value))
+                        Ok(Self::compare_string(
+                            val_string, compare_op, comp_value, false,
+                        ))
                     }
+                    // the resource value is a date. Since the comparison value is not, this is an error.
+                    AttrValue::DateTime(_) => Err(Error::MalformedDatetime(comp_value.to_string())),
                     value => Err(Error::MalformedString(value.to_string())),
                 }
             }
         }
     }
 
+    pub fn do_compare_with_definition(
+        &self,
+        compare_op: &CompareOp,
+        resource_value: &AttrValue,
+        definition: &AttributeDefinition,
+        policy: MismatchPolicy,
+    ) -> MatcherResult<bool> {
+        match (self, resource_value) {
+            (CompValue::Null, _) => Self::compare_null(resource_value, compare_op),
+            (_, AttrValue::Multi(values)) => Self::compare_multi_valued(values, policy, |value| {
+                self.do_compare_with_definition(compare_op, value, definition, policy)
+            }),
+            _ => match definition.attr_type {
+                AttributeType::Boolean => self.compare_as_boolean(compare_op, resource_value),
+                AttributeType::Decimal | AttributeType::Integer => {
+                    self.compare_as_number(compare_op, resource_value)
+                }
+                AttributeType::DateTime => self.compare_as_datetime(compare_op, resource_value),
+                AttributeType::String | AttributeType::Binary | AttributeType::Reference => {
+                    self.compare_as_string(compare_op, resource_value, definition.case_exact)
+                }
+                AttributeType::Complex => {
+                    Err(Error::InvalidComparisonValue(resource_value.to_string()))
+                }
+            },
+        }
+    }
+
+    fn compare_as_boolean(
+        &self,
+        compare_op: &CompareOp,
+        resource_value: &AttrValue,
+    ) -> MatcherResult<bool> {
+        match (self, resource_value) {
+            (CompValue::True, AttrValue::Bool(value)) => Self::compare_true(*value, compare_op),
+            (CompValue::False, AttrValue::Bool(value)) => Self::compare_false(*value, compare_op),
+            (CompValue::True | CompValue::False, value) => {
+                Err(Error::MalformedBoolean(value.to_string()))
+            }
+            (comp_value, _) => Err(Error::MalformedBoolean(comp_value.to_string())),
+        }
+    }
+
+    fn compare_as_number(
+        &self,
+        compare_op: &CompareOp,
+        resource_value: &AttrValue,
+    ) -> MatcherResult<bool> {
+        match (self, resource_value) {
+            (CompValue::Number(comp_value), AttrValue::Number(value)) => {
+                Self::compare_number(value, compare_op, comp_value)
+            }
+            (CompValue::Number(_), value) => Err(Error::MalformedNumber(value.to_string())),
+            (comp_value, _) => Err(Error::MalformedNumber(comp_value.to_string())),
+        }
+    }
+
+    fn compare_as_datetime(
+        &self,
+        compare_op: &CompareOp,
+        resource_value: &AttrValue,
+    ) -> MatcherResult<bool> {
+        let comp_value = match self {
+            CompValue::String(comp_value) => Self::convert_str_to_datetime(comp_value),
+            _ => None,
+        }
+        .ok_or_else(|| Error::MalformedDatetime(self.to_string()))?;
+        match resource_value {
+            AttrValue::DateTime(value) => Self::compare_datetime(value, compare_op, &comp_value),
+            AttrValue::String(value) => Self::convert_str_to_datetime(value)
+                .ok_or_else(|| Error::MalformedDatetime(value.to_string()))
+                .and_then(|value| Self::compare_datetime(&value, compare_op, &comp_value)),
+            value => Err(Error::MalformedDatetime(value.to_string())),
+        }
+    }
+
+    fn compare_as_string(
+        &self,
+        compare_op: &CompareOp,
+        resource_value: &AttrValue,
+        case_exact: bool,
+    ) -> MatcherResult<bool> {
+        match (self, resource_value) {
+            (CompValue::String(comp_value), AttrValue::String(value)) => Ok(Self::compare_string(
+                value, compare_op, comp_value, case_exact,
+            )),
+            (CompValue::String(_), value) => Err(Error::MalformedString(value.to_string())),
+            (comp_value, _) => Err(Error::MalformedString(comp_value.to_string())),
+        }
+    }
+
+    /// a multi-valued attribute matches if any of its values matches, the policy applies to each
+    /// value, so that with [`MismatchPolicy::NoMatch`] a value that can't be compared doesn't match
+    fn compare_multi_valued(
+        values: &[AttrValue],
+        policy: MismatchPolicy,
+        compare: impl Fn(&AttrValue) -> MatcherResult<bool>,
+    ) -> MatcherResult<bool> {
+        values
+            .iter()
+            .map(|value| Self::apply_mismatch_policy(policy, compare(value)))
+            .collect::<MatcherResult<Vec<bool>>>()
+            .map(|results| results.into_iter().any(identity))
+    }
+
     fn compare_orderable_values<R: PartialEq + PartialOrd<C>, C: PartialEq>(
         resource_value: &R,
         compare_op: &CompareOp,
@@ -404,20 +803,6 @@ impl<'a> CompValue<'a> {
         }
     }
 
-    fn convert_number_to_decimal(n: &Number) -> Option<Decimal> {
-        if let Some(value_i64) = n.as_i64() {
-            return Decimal::from_i64(value_i64);
-        }
-        if let Some(value_u64) = n.as_u64() {
-            return Decimal::from_u64(value_u64);
-        }
-        if let Some(value_f64) = n.as_f64() {
-            return Decimal::from_f64(value_f64);
-        }
-
-        None
-    }
-
     fn convert_str_to_decimal(n: &str) -> Option<Decimal> {
         Decimal::from_str_exact(n).ok()
     }
@@ -1,147 +1,474 @@
+use std::borrow::Cow;
+
 use nom::branch::alt;
-use nom::bytes::complete::{is_not, tag, tag_no_case, take};
-use nom::character::complete::{alpha1, alphanumeric1, char, digit1, space0, space1};
-use nom::combinator::{map, map_res, opt, recognize, value};
-use nom::multi::{many0, many1};
-use nom::sequence::{delimited, pair, preceded, separated_pair, terminated, tuple};
-use nom::IResult;
+use nom::bytes::complete::{tag, tag_no_case, take, take_while, take_while1, take_while_m_n};
+use nom::character::complete::{
+    alpha1, alphanumeric1, char, digit0, digit1, one_of, satisfy, space0, space1,
+};
+use nom::combinator::{cut, map, map_opt, map_res, opt, recognize, value};
+use nom::error::{context, ErrorKind, ParseError as _};
+use nom::multi::{fold_many0, many0, many1};
+use nom::sequence::{pair, preceded, separated_pair, terminated, tuple};
 use rust_decimal::Decimal;
 
 use super::*;
 
-pub fn filter(i: &str) -> IResult<&str, Filter<'_>> {
+/// `FILTER = orExp`, the lowest precedence level of the grammar.
+///
+/// RFC 7644 gives `and` a higher precedence than `or`, so the grammar is split in levels:
+/// an `or` expression is made of `and` expressions, which in turn are made of terms
+/// (attribute expressions, value paths and parenthesised filters).
+pub fn filter(i: &str) -> ParserResult<'_, Filter<'_>> {
+    or_exp(i)
+}
+
+fn or_exp(i: &str) -> ParserResult<'_, Filter<'_>> {
+    map(
+        pair(
+            and_exp,
+            opt(preceded(
+                log_exp_separator(LogExpOperator::Or),
+                log_exp_operand(or_exp),
+            )),
+        ),
+        |(left, right)| match right {
+            Some(right) => Filter::LogExp(LogExpData::new((left, LogExpOperator::Or, right))),
+            None => left,
+        },
+    )(i)
+}
+
+fn and_exp(i: &str) -> ParserResult<'_, Filter<'_>> {
+    map(
+        pair(
+            filter_term,
+            opt(preceded(
+                log_exp_separator(LogExpOperator::And),
+                log_exp_operand(and_exp),
+            )),
+        ),
+        |(left, right)| match right {
+            Some(right) => Filter::LogExp(LogExpData::new((left, LogExpOperator::And, right))),
+            None => left,
+        },
+    )(i)
+}
+
+fn filter_term(i: &str) -> ParserResult<'_, Filter<'_>> {
     alt((
-        map(log_exp_data, Filter::LogExp),
         map(attr_exp_data, Filter::AttrExp),
         map(value_path_data, Filter::ValuePath),
         map(
             tuple((
                 map(opt(tag_no_case("not")), |not| not.is_some()),
                 space0,
-                delimited(char('('), filter, char(')')),
+                parenthesized(filter),
             )),
             |(not, _, filter)| Filter::sub_filter((not, filter)),
         ),
     ))(i)
 }
 
-pub fn attr_exp_data(i: &str) -> IResult<&str, AttrExpData<'_>> {
+fn parenthesized<'a, O>(
+    parser: impl FnMut(&'a str) -> ParserResult<'a, O>,
+) -> impl FnMut(&'a str) -> ParserResult<'a, O> {
+    preceded(
+        context("opening parenthesis", char('(')),
+        cut(terminated(
+            parser,
+            context("closing parenthesis", char(')')),
+        )),
+    )
+}
+
+pub fn attr_exp_data(i: &str) -> ParserResult<'_, AttrExpData<'_>> {
     alt((
         map(
-            tuple((attr_path, space1, tag_no_case("pr"))),
-            |(attr_path, _, _)| AttrExpData::Present(attr_path),
+            tuple((
+                attr_path,
+                context("presence operator", preceded(space1, tag_no_case("pr"))),
+            )),
+            |(attr_path, _)| AttrExpData::Present(attr_path),
         ),
         map(
-            tuple((attr_path, space1, compare_op, space1, comp_value)),
-            |(attr_path, _, compare_op, _, comp_value)| {
+            tuple((
+                attr_path,
+                context("comparison operator", preceded(space1, compare_op)),
+                context("comparison value", preceded(space1, comp_value)),
+            )),
+            |(attr_path, compare_op, comp_value)| {
                 AttrExpData::Compare(attr_path, compare_op, comp_value)
             },
         ),
     ))(i)
 }
 
-pub fn log_exp_data(i: &str) -> IResult<&str, LogExpData<'_>> {
+/// A logical expression where both sides are bound by the same operator.
+///
+/// Mixed `and`/`or` chains are resolved by [`filter`] according to operator precedence,
+/// so this only recognises `FILTER SP ("and" / "or") SP FILTER` at the top level of the input.
+pub fn log_exp_data(i: &str) -> ParserResult<'_, LogExpData<'_>> {
+    map_res(filter, |filter| match filter {
+        Filter::LogExp(log_exp_data) => Ok(log_exp_data),
+        _ => Err(()),
+    })(i)
+}
+
+/// The right side of a logical expression. Once a logical operator is found
+/// an operand must follow, so there is no backtracking from here.
+fn log_exp_operand<'a, O>(
+    parser: impl FnMut(&'a str) -> ParserResult<'a, O>,
+) -> impl FnMut(&'a str) -> ParserResult<'a, O> {
+    cut(context("filter expression", preceded(space1, parser)))
+}
+
+fn log_exp_separator<'a>(
+    operator: LogExpOperator,
+) -> impl FnMut(&'a str) -> ParserResult<'a, LogExpOperator> {
+    move |i| {
+        map_res(
+            preceded(space1, log_exp_operator),
+            |parsed: LogExpOperator| {
+                if parsed == operator {
+                    Ok(parsed)
+                } else {
+                    Err(())
+                }
+            },
+        )(i)
+    }
+}
+
+pub fn value_path_data(i: &str) -> ParserResult<'_, ValuePathData<'_>> {
     map(
         tuple((
-            alt((
-                map(attr_exp_data, Filter::AttrExp),
-                map(value_path_data, Filter::ValuePath),
-                map(
-                    tuple((
-                        map(opt(tag_no_case("not")), |not| not.is_some()),
-                        space0,
-                        delimited(char('('), filter, char(')')),
-                    )),
-                    |(not, _, filter)| Filter::sub_filter((not, filter)),
-                ),
-            )),
-            space1,
-            log_exp_operator,
-            space1,
-            filter,
+            attr_path,
+            preceded(
+                context("opening bracket", char('[')),
+                cut(terminated(
+                    value_filter,
+                    context("closing bracket", char(']')),
+                )),
+            ),
         )),
-        |(left, _, log_exp_operator, _, right)| LogExpData::new((left, log_exp_operator, right)),
+        ValuePathData::new,
     )(i)
 }
 
-pub fn value_path_data(i: &str) -> IResult<&str, ValuePathData<'_>> {
+/// `PATH = attrPath / valuePath [subAttr]`, the path of a PATCH operation
+pub fn patch_path(i: &str) -> ParserResult<'_, PatchPath<'_>> {
+    alt((
+        map(pair(value_path_data, opt(sub_attr)), PatchPath::value_path),
+        map(attr_path, PatchPath::Attribute),
+    ))(i)
+}
+
+/// `valFilter`, the filter inside the square brackets of a value path.
+///
+/// It follows the same precedence rules as [`filter`], `and` binds tighter than `or`.
+pub fn value_filter(i: &str) -> ParserResult<'_, ValFilter<'_>> {
+    val_or_exp(i)
+}
+
+fn val_or_exp(i: &str) -> ParserResult<'_, ValFilter<'_>> {
     map(
-        tuple((attr_path, delimited(char('['), value_filter, char(']')))),
-        ValuePathData::new,
+        pair(
+            val_and_exp,
+            opt(preceded(
+                log_exp_separator(LogExpOperator::Or),
+                log_exp_operand(val_or_exp),
+            )),
+        ),
+        |(left, right)| match right {
+            Some(right) => ValFilter::log_exp(LogExpData::new((
+                val_filter_into_filter(left),
+                LogExpOperator::Or,
+                val_filter_into_filter(right),
+            ))),
+            None => left,
+        },
     )(i)
 }
 
-pub fn value_filter(i: &str) -> IResult<&str, ValFilter<'_>> {
+fn val_and_exp(i: &str) -> ParserResult<'_, ValFilter<'_>> {
+    map(
+        pair(
+            val_filter_term,
+            opt(preceded(
+                log_exp_separator(LogExpOperator::And),
+                log_exp_operand(val_and_exp),
+            )),
+        ),
+        |(left, right)| match right {
+            Some(right) => ValFilter::log_exp(LogExpData::new((
+                val_filter_into_filter(left),
+                LogExpOperator::And,
+                val_filter_into_filter(right),
+            ))),
+            None => left,
+        },
+    )(i)
+}
+
+fn val_filter_term(i: &str) -> ParserResult<'_, ValFilter<'_>> {
     alt((
-        map(log_exp_data, ValFilter::log_exp),
         map(attr_exp_data, ValFilter::attr_exp),
         map(
             separated_pair(
                 map(opt(tag_no_case("not")), |not| not.is_some()),
                 space0,
-                delimited(char('('), value_filter, char(')')),
+                parenthesized(value_filter),
             ),
             ValFilter::sub_filter,
         ),
     ))(i)
 }
 
-pub fn attr_path(i: &str) -> IResult<&str, AttrPath> {
-    map(tuple((opt(uri), attr_name, opt(sub_attr))), AttrPath::new)(i)
+/// [`LogExpData`] holds its operands as [`Filter`], so value filters are converted
+/// when they become the sides of a logical expression.
+fn val_filter_into_filter(val_filter: ValFilter<'_>) -> Filter<'_> {
+    match val_filter {
+        ValFilter::AttrExp(attr_exp_data) => Filter::AttrExp(attr_exp_data),
+        ValFilter::LogExp(log_exp_data) => Filter::LogExp(log_exp_data),
+        ValFilter::SubFilter(not, val_filter) => {
+            Filter::sub_filter((not, val_filter_into_filter(*val_filter)))
+        }
+    }
+}
+
+pub fn attr_path(i: &str) -> ParserResult<'_, AttrPath> {
+    context(
+        "attribute path",
+        map(tuple((opt(uri), attr_name, opt(sub_attr))), AttrPath::new),
+    )(i)
 }
 
-pub fn uri(i: &str) -> IResult<&str, Uri> {
+/// A URI following the RFC 3986 grammar, terminated by the colon that separates it from the
+/// attribute name. The terminating colon is not part of the URI.
+///
+/// The URI is made of a scheme and of segments separated by colons, each made of the unreserved,
+/// percent-encoded and delimiter characters. Parentheses and square brackets are left out, since in a
+/// filter they group expressions and start a value filter.
+pub fn uri(i: &str) -> ParserResult<'_, Uri> {
     map(
-        many1(terminated(many1(alt((alphanumeric1, tag(".")))), tag(":"))),
-        |namespaces| {
-            let uri: String = namespaces
-                .into_iter()
-                .fold("".to_string(), |acc, namespace| {
-                    acc + &namespace.join("") + ":"
-                });
-
-            uri[0..uri.len() - 1].to_string()
-        },
+        recognize(pair(
+            terminated(uri_scheme, char(':')),
+            many0(terminated(uri_segment, char(':'))),
+        )),
+        |uri: &str| uri[0..uri.len() - 1].to_string(),
     )(i)
 }
 
-pub fn compare_op(i: &str) -> IResult<&str, CompareOp> {
+/// `scheme = ALPHA *( ALPHA / DIGIT / "+" / "-" / "." )`
+fn uri_scheme(i: &str) -> ParserResult<'_, &str> {
+    recognize(pair(
+        satisfy(|c| c.is_ascii_alphabetic()),
+        take_while(|c: char| c.is_ascii_alphanumeric() || "+-.".contains(c)),
+    ))(i)
+}
+
+fn uri_segment(i: &str) -> ParserResult<'_, &str> {
+    recognize(many1(alt((
+        take_while1(|c: char| c.is_ascii_alphanumeric() || "-._~!$&'*+,;=@/?#".contains(c)),
+        recognize(pair(
+            char('%'),
+            take_while_m_n(2, 2, |c: char| c.is_ascii_hexdigit()),
+        )),
+    ))))(i)
+}
+
+pub fn compare_op(i: &str) -> ParserResult<'_, CompareOp> {
     map_res(take(2usize), CompareOp::from_str)(i)
 }
 
-pub fn comp_value(i: &str) -> IResult<&str, CompValue<'_>> {
+pub fn comp_value(i: &str) -> ParserResult<'_, CompValue<'_>> {
     alt((
         value(CompValue::False, tag("false")),
         value(CompValue::Null, tag("null")),
         value(CompValue::True, tag("true")),
-        map(
-            map_res(many1(alt((digit1, tag(".")))), |digit| {
-                Decimal::from_str(&digit.join(""))
-            }),
-            CompValue::Number,
-        ),
-        map(
-            delimited(char('"'), recognize(is_not("\"")), char('"')),
-            CompValue::String,
+        map(number_value, CompValue::Number),
+        map(string_value, CompValue::String),
+    ))(i)
+}
+
+/// A number literal following the RFC 7159 grammar, `[ minus ] int [ frac ] [ exp ]`.
+///
+/// Digits beyond the precision of a [`Decimal`] are rounded, so a number too small to be
+/// represented is zero. A number too large for a [`Decimal`] is a failure with
+/// [`ErrorKind::TooLarge`], so that no other branch gets a chance to parse it.
+pub fn number_value(i: &str) -> ParserResult<'_, Decimal> {
+    let (remain, number) = json_number(i)?;
+    match decimal_from_number(number) {
+        Some(decimal) => Ok((remain, decimal)),
+        None => Err(nom::Err::Failure(InputError::from_error_kind(
+            i,
+            ErrorKind::TooLarge,
+        ))),
+    }
+}
+
+/// Converts a number recognised by [`json_number`] to a [`Decimal`], `None` if it's too large.
+///
+/// The significant digits and the exponent are normalized first, so that trailing zeros and a
+/// zero mantissa don't count against the range of a [`Decimal`].
+fn decimal_from_number(number: &str) -> Option<Decimal> {
+    let (negative, number) = match number.strip_prefix('-') {
+        Some(number) => (true, number),
+        None => (false, number),
+    };
+    let (mantissa, exponent) = match number.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, exponent),
+        None => (number, "0"),
+    };
+    let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let digits = format!("{int}{frac}");
+    let digits = digits.trim_start_matches('0');
+    let significant = digits.trim_end_matches('0');
+    if significant.is_empty() {
+        return Some(Decimal::ZERO);
+    }
+    // the value is `significant * 10^exponent`, an exponent out of the i64 range is saturated
+    let saturated = if exponent.starts_with('-') {
+        i64::MIN
+    } else {
+        i64::MAX
+    };
+    let exponent = exponent
+        .parse::<i64>()
+        .unwrap_or(saturated)
+        .saturating_sub(frac.len() as i64)
+        .saturating_add((digits.len() - significant.len()) as i64);
+    // the position of the most significant digit, 0 for the units
+    let magnitude = exponent.saturating_add(significant.len() as i64 - 1);
+    let sign = if negative { "-" } else { "" };
+    let decimal = if magnitude >= MAX_DECIMAL_DIGITS {
+        return None;
+    } else if magnitude < -MAX_DECIMAL_DIGITS - 1 {
+        // below half of the smallest representable value
+        return Some(Decimal::ZERO);
+    } else if exponent >= 0 {
+        format!("{sign}{significant}{}", "0".repeat(exponent as usize))
+    } else if magnitude >= 0 {
+        let (int, frac) = significant.split_at(magnitude as usize + 1);
+        format!("{sign}{int}.{frac}")
+    } else {
+        let zeros = "0".repeat((-magnitude - 1) as usize);
+        format!("{sign}0.{zeros}{significant}")
+    };
+    // the excess fraction digits are rounded, only an integer part too large is an error
+    decimal.parse().ok()
+}
+
+/// The number of digits of the largest [`Decimal`]
+const MAX_DECIMAL_DIGITS: i64 = 29;
+
+/// Recognises the text of a number literal, without converting it.
+pub fn json_number(i: &str) -> ParserResult<'_, &str> {
+    recognize(tuple((
+        opt(char('-')),
+        alt((
+            tag("0"),
+            recognize(pair(satisfy(|c| ('1'..='9').contains(&c)), digit0)),
+        )),
+        opt(pair(char('.'), digit1)),
+        opt(tuple((one_of("eE"), opt(one_of("+-")), digit1))),
+    )))(i)
+}
+
+/// A string literal following the RFC 7159 grammar.
+///
+/// The content is returned unescaped. It borrows from the input unless an escape sequence
+/// forces an owned allocation.
+pub fn string_value(i: &str) -> ParserResult<'_, Cow<'_, str>> {
+    preceded(
+        char('"'),
+        cut(terminated(
+            fold_many0(
+                alt((
+                    map(unescaped_chars, Cow::Borrowed),
+                    map(escaped_char, Cow::Owned),
+                )),
+                || Cow::Borrowed(""),
+                |mut acc: Cow<'_, str>, chunk| {
+                    if acc.is_empty() {
+                        chunk
+                    } else {
+                        acc.to_mut().push_str(&chunk);
+                        acc
+                    }
+                },
+            ),
+            context("closing quote", char('"')),
+        )),
+    )(i)
+}
+
+fn unescaped_chars(i: &str) -> ParserResult<'_, &str> {
+    take_while1(|c: char| c != '"' && c != '\\' && c >= '\u{20}')(i)
+}
+
+fn escaped_char(i: &str) -> ParserResult<'_, String> {
+    preceded(
+        char('\\'),
+        cut(context(
+            "escape sequence",
+            alt((
+                map(
+                    alt((
+                        value('"', char('"')),
+                        value('\\', char('\\')),
+                        value('/', char('/')),
+                        value('\u{08}', char('b')),
+                        value('\u{0C}', char('f')),
+                        value('\n', char('n')),
+                        value('\r', char('r')),
+                        value('\t', char('t')),
+                    )),
+                    String::from,
+                ),
+                map(preceded(char('u'), unicode_escape), String::from),
+            )),
+        )),
+    )(i)
+}
+
+/// The content of a `\uXXXX` escape. Characters outside the basic multilingual plane
+/// are written as a surrogate pair, so a high surrogate must be followed by a second escape.
+fn unicode_escape(i: &str) -> ParserResult<'_, char> {
+    alt((
+        map_opt(
+            separated_pair(hex_code_unit, tag("\\u"), hex_code_unit),
+            |(high, low)| match char::decode_utf16([high, low]).collect::<Vec<_>>()[..] {
+                [Ok(decoded)] => Some(decoded),
+                _ => None,
+            },
         ),
+        map_opt(hex_code_unit, |code_unit| char::from_u32(code_unit as u32)),
     ))(i)
 }
 
-pub fn log_exp_operator(i: &str) -> IResult<&str, LogExpOperator> {
+fn hex_code_unit(i: &str) -> ParserResult<'_, u16> {
+    map_res(
+        take_while_m_n(4, 4, |c: char| c.is_ascii_hexdigit()),
+        |hex| u16::from_str_radix(hex, 16),
+    )(i)
+}
+
+pub fn log_exp_operator(i: &str) -> ParserResult<'_, LogExpOperator> {
     alt((
         value(LogExpOperator::And, tag_no_case("and")),
         value(LogExpOperator::Or, tag_no_case("or")),
     ))(i)
 }
 
-pub fn name_char(i: &str) -> IResult<&str, Vec<NameChar>> {
+pub fn name_char(i: &str) -> ParserResult<'_, Vec<NameChar<'_>>> {
     many0(alt((alphanumeric1, tag("_"), tag("-"))))(i)
 }
 
-pub fn attr_name(i: &str) -> IResult<&str, AttrName> {
+pub fn attr_name(i: &str) -> ParserResult<'_, AttrName> {
     map(pair(alpha1, name_char), AttrName::new)(i)
 }
 
-pub fn sub_attr(i: &str) -> IResult<&str, SubAttr> {
+pub fn sub_attr(i: &str) -> ParserResult<'_, SubAttr> {
     preceded(char('.'), attr_name)(i)
 }
@@ -0,0 +1,85 @@
+use std::cmp::Ordering;
+
+use nom::error::{ContextError, ErrorKind, FromExternalError, ParseError as NomParseError};
+use nom::IResult;
+
+use crate::error::ParseError;
+
+pub type ParserResult<'a, O> = IResult<&'a str, O, InputError<'a>>;
+
+/// The error produced by the combinator functions.
+///
+/// It keeps track of the remaining input where parsing failed and of the tokens that were expected
+/// there. When several branches fail, the one that went further in the input wins, and the expected
+/// tokens of branches failing at the same position are merged.
+#[derive(Debug, PartialEq)]
+pub struct InputError<'a> {
+    input: &'a str,
+    kind: ErrorKind,
+    expected: Vec<&'static str>,
+}
+
+impl<'a> InputError<'a> {
+    pub fn input(&self) -> &'a str {
+        self.input
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    pub fn expected(&self) -> &[&'static str] {
+        &self.expected
+    }
+
+    /// converts this error in a [`ParseError`], positioned relatively to the whole filter
+    pub fn into_parse_error(self, filter: &str) -> ParseError {
+        ParseError::new(filter, self.input, self.expected)
+    }
+}
+
+impl<'a> NomParseError<&'a str> for InputError<'a> {
+    fn from_error_kind(input: &'a str, kind: ErrorKind) -> Self {
+        Self {
+            input,
+            kind,
+            expected: vec![],
+        }
+    }
+
+    fn append(_input: &'a str, _kind: ErrorKind, other: Self) -> Self {
+        other
+    }
+
+    fn or(mut self, other: Self) -> Self {
+        // the shorter the remaining input, the further the parser went
+        match self.input.len().cmp(&other.input.len()) {
+            Ordering::Less => self,
+            Ordering::Greater => other,
+            Ordering::Equal => {
+                for expected in other.expected {
+                    if !self.expected.contains(&expected) {
+                        self.expected.push(expected);
+                    }
+                }
+                self
+            }
+        }
+    }
+}
+
+impl<'a> ContextError<&'a str> for InputError<'a> {
+    fn add_context(input: &'a str, ctx: &'static str, mut other: Self) -> Self {
+        // a context only describes the expected token if nothing at all has been parsed from it
+        if input.len() == other.input.len() || other.expected.is_empty() {
+            other.expected = vec![ctx];
+        }
+        other
+    }
+}
+
+impl<'a, E> FromExternalError<&'a str, E> for InputError<'a> {
+    fn from_external_error(input: &'a str, kind: ErrorKind, _e: E) -> Self {
+        Self::from_error_kind(input, kind)
+    }
+}
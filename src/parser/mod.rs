@@ -1,12 +1,21 @@
 use std::str::FromStr;
 
 pub use combinator_functions::*;
+pub use input_error::*;
 pub use model::*;
 
 #[cfg(test)]
 #[path = "test/parser_test.rs"]
 mod parser_test;
 
+#[cfg(test)]
+#[path = "test/printer_test.rs"]
+mod printer_test;
+
 mod model;
 
 mod combinator_functions;
+
+mod input_error;
+
+mod printer;
@@ -1,13 +1,16 @@
+use std::borrow::Cow;
 use std::ops::Deref;
 use std::str::FromStr;
 
+use nom::error::ErrorKind;
 use nom::Finish;
 use rust_decimal::Decimal;
 
-use super::filter;
+use super::{attr_path, filter, json_number, patch_path, ParserResult};
+use crate::error::ParseError;
 use crate::Error;
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum Filter<'a> {
     AttrExp(AttrExpData<'a>),
     LogExp(LogExpData<'a>),
@@ -19,15 +22,44 @@ impl<'a> Filter<'a> {
     pub fn sub_filter((not, filter): (bool, Filter<'a>)) -> Self {
         Self::Sub(not, Box::new(filter))
     }
+
+    /// detaches the filter from the input it has been parsed from, so that it can be stored
+    /// or moved to another thread
+    pub fn into_owned(self) -> Filter<'static> {
+        match self {
+            Filter::AttrExp(attr_exp_data) => Filter::AttrExp(attr_exp_data.into_owned()),
+            Filter::LogExp(log_exp_data) => Filter::LogExp(log_exp_data.into_owned()),
+            Filter::ValuePath(value_path_data) => Filter::ValuePath(value_path_data.into_owned()),
+            Filter::Sub(not, filter) => Filter::Sub(not, Box::new(filter.into_owned())),
+        }
+    }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum AttrExpData<'a> {
     Present(AttrPath),
     Compare(AttrPath, CompareOp, CompValue<'a>),
 }
 
-#[derive(Debug, PartialEq)]
+impl<'a> AttrExpData<'a> {
+    pub fn attr_path(&self) -> &AttrPath {
+        match self {
+            AttrExpData::Present(attr_path) => attr_path,
+            AttrExpData::Compare(attr_path, _, _) => attr_path,
+        }
+    }
+
+    pub fn into_owned(self) -> AttrExpData<'static> {
+        match self {
+            AttrExpData::Present(attr_path) => AttrExpData::Present(attr_path),
+            AttrExpData::Compare(attr_path, compare_op, comp_value) => {
+                AttrExpData::Compare(attr_path, compare_op, comp_value.into_owned())
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
 pub struct AttrPath {
     uri: Option<Uri>,
     attr_name: AttrName,
@@ -43,6 +75,9 @@ impl AttrPath {
         }
     }
 
+    pub fn uri(&self) -> &Option<Uri> {
+        &self.uri
+    }
     pub fn attr_name(&self) -> &AttrName {
         &self.attr_name
     }
@@ -83,10 +118,27 @@ impl FromStr for CompareOp {
     }
 }
 
+impl CompareOp {
+    /// the operator as it's written in a filter
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Equal => "eq",
+            Self::NotEqual => "ne",
+            Self::Contains => "co",
+            Self::StartsWith => "sw",
+            Self::EndsWith => "ew",
+            Self::GreaterThan => "gt",
+            Self::GreaterThanOrEqual => "ge",
+            Self::LessThan => "lt",
+            Self::LessThanOrEqual => "le",
+        }
+    }
+}
+
 // https://datatracker.ietf.org/doc/html/rfc3986#appendix-A
 pub type Uri = String;
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct AttrName(pub(crate) String);
 
 impl AttrName {
@@ -99,7 +151,8 @@ impl AttrName {
     }
 
     #[cfg(test)]
-    pub fn from_str<'a>(name: &str) -> Self {
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(name: &str) -> Self {
         Self(name.to_string())
     }
 }
@@ -125,10 +178,23 @@ pub enum CompValue<'a> {
     Null,
     True,
     Number(Decimal),
-    String(&'a str),
+    /// the unescaped content of the string literal, borrowed from the input when it contains no escape sequences
+    String(Cow<'a, str>),
 }
 
-#[derive(Debug, PartialEq)]
+impl<'a> CompValue<'a> {
+    pub fn into_owned(self) -> CompValue<'static> {
+        match self {
+            CompValue::False => CompValue::False,
+            CompValue::Null => CompValue::Null,
+            CompValue::True => CompValue::True,
+            CompValue::Number(number) => CompValue::Number(number),
+            CompValue::String(string) => CompValue::String(Cow::Owned(string.into_owned())),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
 pub struct LogExpData<'a> {
     pub left: Box<Filter<'a>>,
     pub log_exp_operator: LogExpOperator,
@@ -143,6 +209,14 @@ impl<'a> LogExpData<'a> {
             right: Box::new(right),
         }
     }
+
+    pub fn into_owned(self) -> LogExpData<'static> {
+        LogExpData {
+            left: Box::new(self.left.into_owned()),
+            log_exp_operator: self.log_exp_operator,
+            right: Box::new(self.right.into_owned()),
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -164,7 +238,7 @@ impl LogExpOperator {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct ValuePathData<'a> {
     attr_path: AttrPath,
     val_filter: ValFilter<'a>,
@@ -183,9 +257,16 @@ impl<'a> ValuePathData<'a> {
     pub fn val_filter(&self) -> &ValFilter<'a> {
         &self.val_filter
     }
+
+    pub fn into_owned(self) -> ValuePathData<'static> {
+        ValuePathData {
+            attr_path: self.attr_path,
+            val_filter: self.val_filter.into_owned(),
+        }
+    }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum ValFilter<'a> {
     AttrExp(AttrExpData<'a>),
     LogExp(LogExpData<'a>),
@@ -204,17 +285,89 @@ impl<'a> ValFilter<'a> {
     pub fn sub_filter((not, val_filter): (bool, ValFilter<'a>)) -> Self {
         Self::SubFilter(not, Box::new(val_filter))
     }
+
+    pub fn into_owned(self) -> ValFilter<'static> {
+        match self {
+            ValFilter::AttrExp(attr_exp_data) => ValFilter::AttrExp(attr_exp_data.into_owned()),
+            ValFilter::LogExp(log_exp_data) => ValFilter::LogExp(log_exp_data.into_owned()),
+            ValFilter::SubFilter(not, val_filter) => {
+                ValFilter::SubFilter(not, Box::new(val_filter.into_owned()))
+            }
+        }
+    }
+}
+
+/// The `path` of a PATCH operation, RFC 7644 section 3.5.2:
+/// `PATH = attrPath / valuePath [subAttr]`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PatchPath<'a> {
+    /// an attribute, or a sub-attribute, like `name.familyName`
+    Attribute(AttrPath),
+    /// the values of a multi-valued attribute selected by a filter, or one of their
+    /// sub-attributes, like `members[value eq "2819c223"].display`
+    ValuePath(ValuePathData<'a>, Option<SubAttr>),
+}
+
+impl<'a> PatchPath<'a> {
+    pub fn value_path((value_path_data, sub_attr): (ValuePathData<'a>, Option<SubAttr>)) -> Self {
+        Self::ValuePath(value_path_data, sub_attr)
+    }
+
+    /// the path of the attribute the operation applies to
+    pub fn attr_path(&self) -> &AttrPath {
+        match self {
+            PatchPath::Attribute(attr_path) => attr_path,
+            PatchPath::ValuePath(value_path_data, _) => value_path_data.attr_path(),
+        }
+    }
+
+    pub fn into_owned(self) -> PatchPath<'static> {
+        match self {
+            PatchPath::Attribute(attr_path) => PatchPath::Attribute(attr_path),
+            PatchPath::ValuePath(value_path_data, sub_attr) => {
+                PatchPath::ValuePath(value_path_data.into_owned(), sub_attr)
+            }
+        }
+    }
 }
 
 /// main API entrance for this module, given a filter string,
 /// it generates an Result with a possible parsed Expression struct
-pub(crate) fn scim_filter_parser(input: &str) -> Result<Filter, Error> {
-    let (remain, expression) = filter(input).map_err(|e| e.to_owned()).finish()?;
+pub fn scim_filter_parser(input: &str) -> Result<Filter<'_>, Error> {
+    parse_complete(input, filter, "logical operator")
+}
+
+/// parses the `path` of a PATCH operation, like `members[value eq "2819c223"].display`
+pub fn patch_path_parser(input: &str) -> Result<PatchPath<'_>, Error> {
+    parse_complete(input, patch_path, "end of path")
+}
+
+/// parses an attribute path on its own, like the `sortBy` parameter of a query
+pub fn attr_path_parser(input: &str) -> Result<AttrPath, Error> {
+    parse_complete(input, attr_path, "end of attribute path")
+}
+
+/// runs the parser on the whole input, `expected` describes what could follow the parsed part
+fn parse_complete<'a, O>(
+    input: &'a str,
+    mut parser: impl FnMut(&'a str) -> ParserResult<'a, O>,
+    expected: &'static str,
+) -> Result<O, Error> {
+    let (remain, parsed) =
+        parser(input)
+            .finish()
+            .map_err(|e| match (e.kind(), json_number(e.input())) {
+                (ErrorKind::TooLarge, Ok((_, number))) => {
+                    Error::NumberOutOfRange(number.to_owned())
+                }
+                _ => Error::Parser(e.into_parse_error(input)),
+            })?;
     if !remain.is_empty() {
-        return Err(Error::WrongFilterFormat(
-            input.to_owned(),
-            remain.to_owned(),
-        ));
+        return Err(Error::Parser(ParseError::new(
+            input,
+            remain.trim_start(),
+            vec![expected],
+        )));
     }
-    Ok(expression)
+    Ok(parsed)
 }
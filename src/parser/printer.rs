@@ -0,0 +1,133 @@
+//! Prints a parsed filter back to its canonical text.
+//!
+//! Operators are lowercase, tokens are separated by a single space, and parentheses are only written
+//! where the tree has a [`Filter::Sub`] node or where they are needed to keep the `and` over `or`
+//! precedence. The text of a parsed filter re-parses to an equal tree.
+
+use std::fmt::{Display, Formatter, Result};
+
+use super::*;
+
+impl<'a> Display for Filter<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            Filter::AttrExp(attr_exp_data) => attr_exp_data.fmt(f),
+            Filter::LogExp(log_exp_data) => log_exp_data.fmt(f),
+            Filter::ValuePath(value_path_data) => value_path_data.fmt(f),
+            Filter::Sub(is_not, filter) => fmt_sub_filter(f, *is_not, filter),
+        }
+    }
+}
+
+impl<'a> Display for AttrExpData<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            AttrExpData::Present(attr_path) => write!(f, "{} pr", attr_path),
+            AttrExpData::Compare(attr_path, compare_op, comp_value) => {
+                write!(f, "{} {} {}", attr_path, compare_op.as_str(), comp_value)
+            }
+        }
+    }
+}
+
+impl Display for AttrPath {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        if let Some(uri) = self.uri() {
+            write!(f, "{}:", uri)?;
+        }
+        write!(f, "{}", self.attr_name())?;
+        if let Some(sub_attr) = self.sub_attr() {
+            write!(f, ".{}", sub_attr)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for AttrName {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl<'a> Display for CompValue<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            CompValue::False => f.write_str("false"),
+            CompValue::Null => f.write_str("null"),
+            CompValue::True => f.write_str("true"),
+            CompValue::Number(number) => write!(f, "{}", number.normalize()),
+            // serde_json escapes the string following the same RFC 7159 grammar the parser reads
+            CompValue::String(string) => {
+                f.write_str(&serde_json::to_string(string).map_err(|_| std::fmt::Error)?)
+            }
+        }
+    }
+}
+
+impl<'a> Display for LogExpData<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        fmt_operand(f, &self.left, &self.log_exp_operator)?;
+        write!(f, " {} ", self.log_exp_operator)?;
+        fmt_operand(f, &self.right, &self.log_exp_operator)
+    }
+}
+
+impl Display for LogExpOperator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            LogExpOperator::And => f.write_str("and"),
+            LogExpOperator::Or => f.write_str("or"),
+        }
+    }
+}
+
+impl<'a> Display for ValuePathData<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "{}[{}]", self.attr_path(), self.val_filter())
+    }
+}
+
+impl<'a> Display for PatchPath<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            PatchPath::Attribute(attr_path) => attr_path.fmt(f),
+            PatchPath::ValuePath(value_path_data, sub_attr) => {
+                value_path_data.fmt(f)?;
+                if let Some(sub_attr) = sub_attr {
+                    write!(f, ".{}", sub_attr)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl<'a> Display for ValFilter<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            ValFilter::AttrExp(attr_exp_data) => attr_exp_data.fmt(f),
+            ValFilter::LogExp(log_exp_data) => log_exp_data.fmt(f),
+            ValFilter::SubFilter(is_not, val_filter) => fmt_sub_filter(f, *is_not, val_filter),
+        }
+    }
+}
+
+fn fmt_sub_filter(f: &mut Formatter<'_>, is_not: bool, filter: &impl Display) -> Result {
+    if is_not {
+        f.write_str("not ")?;
+    }
+    write!(f, "({})", filter)
+}
+
+/// an `or` expression on one side of an `and` needs parentheses, otherwise it would be parsed
+/// with a different precedence
+fn fmt_operand(f: &mut Formatter<'_>, operand: &Filter, parent: &LogExpOperator) -> Result {
+    match operand {
+        Filter::LogExp(log_exp_data)
+            if parent.is_and() && log_exp_data.log_exp_operator.is_or() =>
+        {
+            write!(f, "({})", operand)
+        }
+        _ => write!(f, "{}", operand),
+    }
+}
@@ -1,3 +1,5 @@
+use std::borrow::Cow;
+
 use rust_decimal_macros::dec;
 use test_case::test_case;
 
@@ -6,9 +8,10 @@ use crate::parser::CompareOp::*;
 use crate::parser::Filter::{Sub, ValuePath};
 use crate::parser::LogExpOperator::*;
 use crate::parser::{
-    scim_filter_parser, AttrExpData, AttrName, AttrPath, CompValue, CompareOp, Filter, LogExpData,
-    SubAttr, ValFilter, ValuePathData,
+    patch_path_parser, scim_filter_parser, AttrExpData, AttrName, AttrPath, CompValue, CompareOp,
+    Filter, LogExpData, PatchPath, SubAttr, ValFilter, ValuePathData,
 };
+use crate::Error;
 
 fn attribute_expression<'a>(
     attribute: &'a str,
@@ -16,10 +19,14 @@ fn attribute_expression<'a>(
     value: &'a str,
 ) -> Filter<'a> {
     let attr_path = AttrPath::new((None, AttrName::from_str(attribute), None));
-    Filter::AttrExp(Compare(attr_path, compare_op, CompValue::String(value)))
+    Filter::AttrExp(Compare(
+        attr_path,
+        compare_op,
+        CompValue::String(value.into()),
+    ))
 }
 
-fn attribute_expression_pr(attribute: &str) -> Filter {
+fn attribute_expression_pr(attribute: &str) -> Filter<'_> {
     Filter::AttrExp(Present(AttrPath::new((
         None,
         AttrName::from_str(attribute),
@@ -99,13 +106,30 @@ fn logical_expression_with_more_than_1_and() {
 #[test]
 fn logical_expression_with_more_than_2_and_mixed() {
     let parsed = scim_filter_parser("a eq \"test\" and b ne \"test2\" or c eq \"test3\"");
+    assert_eq!(
+        Filter::LogExp(LogExpData {
+            left: Box::new(Filter::LogExp(LogExpData {
+                left: Box::new(attribute_expression("a", Equal, "test")),
+                log_exp_operator: And,
+                right: Box::new(attribute_expression("b", NotEqual, "test2"))
+            })),
+            log_exp_operator: Or,
+            right: Box::new(attribute_expression("c", Equal, "test3"))
+        }),
+        parsed.unwrap()
+    );
+}
+
+#[test]
+fn and_has_precedence_over_or() {
+    let parsed = scim_filter_parser("a eq \"test\" or b ne \"test2\" and c eq \"test3\"");
     assert_eq!(
         Filter::LogExp(LogExpData {
             left: Box::new(attribute_expression("a", Equal, "test")),
-            log_exp_operator: And,
+            log_exp_operator: Or,
             right: Box::new(Filter::LogExp(LogExpData {
                 left: Box::new(attribute_expression("b", NotEqual, "test2")),
-                log_exp_operator: Or,
+                log_exp_operator: And,
                 right: Box::new(attribute_expression("c", Equal, "test3"))
             }))
         }),
@@ -113,6 +137,51 @@ fn logical_expression_with_more_than_2_and_mixed() {
     );
 }
 
+#[test]
+fn and_has_precedence_over_or_with_multiple_groups() {
+    let parsed = scim_filter_parser("a pr and b pr or c pr and d pr or e pr");
+    assert_eq!(
+        Filter::LogExp(LogExpData {
+            left: Box::new(Filter::LogExp(LogExpData {
+                left: Box::new(attribute_expression_pr("a")),
+                log_exp_operator: And,
+                right: Box::new(attribute_expression_pr("b"))
+            })),
+            log_exp_operator: Or,
+            right: Box::new(Filter::LogExp(LogExpData {
+                left: Box::new(Filter::LogExp(LogExpData {
+                    left: Box::new(attribute_expression_pr("c")),
+                    log_exp_operator: And,
+                    right: Box::new(attribute_expression_pr("d"))
+                })),
+                log_exp_operator: Or,
+                right: Box::new(attribute_expression_pr("e"))
+            }))
+        }),
+        parsed.unwrap()
+    );
+}
+
+#[test]
+fn and_has_precedence_over_or_in_value_filter() {
+    let parsed = scim_filter_parser("emails[type eq \"work\" and primary pr or value pr]");
+    assert_eq!(
+        ValuePath(ValuePathData::new((
+            AttrPath::new((None, AttrName::from_str("emails"), None)),
+            ValFilter::LogExp(LogExpData {
+                left: Box::new(Filter::LogExp(LogExpData {
+                    left: Box::new(attribute_expression("type", Equal, "work")),
+                    log_exp_operator: And,
+                    right: Box::new(attribute_expression_pr("primary"))
+                })),
+                log_exp_operator: Or,
+                right: Box::new(attribute_expression_pr("value"))
+            })
+        ))),
+        parsed.unwrap()
+    );
+}
+
 #[test]
 fn logical_expression_with_parens() {
     let parsed = scim_filter_parser("a eq \"test\" and (b ne \"test2\" or c eq \"test3\")");
@@ -230,7 +299,7 @@ fn not_expressions() {
                             Some(SubAttr::from_str("value"))
                         )),
                         Contains,
-                        CompValue::String("example.org")
+                        CompValue::String("example.org".into())
                     )))
                 }))
             ))
@@ -252,12 +321,42 @@ fn full_attribute_name() {
                 None
             )),
             Equal,
-            CompValue::String("jlennon")
+            CompValue::String("jlennon".into())
         )),
         parsed.unwrap()
     );
 }
 
+#[test_case("urn:acme-corp:scim:ext_1.0:User:costCenter pr", "urn:acme-corp:scim:ext_1.0:User", "costCenter"; "hyphens and underscores")]
+#[test_case("urn:example:%C3%A9:v~1:name pr", "urn:example:%C3%A9:v~1", "name"; "percent-encoded and unreserved characters")]
+#[test_case("https://example.com/scim/v2?x=1&y=2:name pr", "https://example.com/scim/v2?x=1&y=2", "name"; "url")]
+#[test_case("x-acme+ext.v2:name pr", "x-acme+ext.v2", "name"; "scheme characters")]
+fn uri_attribute_name(input: &str, uri: &str, attr_name: &str) {
+    let Filter::AttrExp(Present(attr_path)) = scim_filter_parser(input).unwrap() else {
+        panic!("{} should be a presence expression", input);
+    };
+    assert_eq!(&Some(uri.to_string()), attr_path.uri());
+    assert_eq!(attr_name, &**attr_path.attr_name());
+}
+
+#[test_case("urn:acme:emails[type eq \"work\"]", "urn:acme", "emails"; "value path")]
+#[test_case("urn:acme:name.familyName pr", "urn:acme", "name"; "sub attribute")]
+fn uri_is_split_from_the_attribute(input: &str, uri: &str, attr_name: &str) {
+    let attr_path = match scim_filter_parser(input).unwrap() {
+        Filter::AttrExp(Present(attr_path)) => attr_path,
+        ValuePath(value_path_data) => value_path_data.attr_path().clone(),
+        filter => panic!("{:?} should have an attribute path", filter),
+    };
+    assert_eq!(&Some(uri.to_string()), attr_path.uri());
+    assert_eq!(attr_name, &**attr_path.attr_name());
+}
+
+#[test_case("1urn:a:b pr"; "scheme starting with a digit")]
+#[test_case("urn:a%4:b pr"; "incomplete percent encoding")]
+fn wrong_uri(input: &str) {
+    assert!(scim_filter_parser(input).is_err());
+}
+
 #[test]
 fn decimal_value() {
     let parsed = scim_filter_parser("decimal eq 2.3");
@@ -271,8 +370,191 @@ fn decimal_value() {
     );
 }
 
+#[test_case("a eq 0", dec!(0); "zero")]
+#[test_case("a eq -5", dec!(-5); "negative integer")]
+#[test_case("a eq -0.5", dec!(-0.5); "negative decimal")]
+#[test_case("a eq 1e3", dec!(1000); "exponent")]
+#[test_case("a eq 1E+3", dec!(1000); "uppercase exponent with sign")]
+#[test_case("a eq 1.5e-2", dec!(0.015); "fraction with negative exponent")]
+#[test_case("a eq 0e99999", dec!(0); "zero with large exponent")]
+#[test_case("a eq 1.0e-28", dec!(0.0000000000000000000000000001); "smallest decimal")]
+#[test_case("a eq 1e-40", dec!(0); "exponent too small")]
+#[test_case("a eq 0.12345678901234567890123456789", dec!(0.1234567890123456789012345679); "too many fraction digits")]
+#[test_case("a eq 1000e-3", dec!(1); "trailing zeros")]
+#[test_case("a eq 1e28", dec!(10000000000000000000000000000); "largest exponent")]
+fn number_value(input: &str, expected: rust_decimal::Decimal) {
+    let parsed = scim_filter_parser(input);
+    assert_eq!(
+        Filter::AttrExp(AttrExpData::Compare(
+            AttrPath::new((None, AttrName::from_str("a"), None)),
+            Equal,
+            CompValue::Number(expected)
+        )),
+        parsed.unwrap()
+    );
+}
+
+#[test_case("a eq 1e100"; "exponent too big")]
+#[test_case("a eq -1e29"; "negative exponent too big")]
+#[test_case("a eq 1e99999999999999999999"; "exponent out of range")]
+#[test_case("a eq 123456789012345678901234567890123"; "too many digits")]
+fn number_value_out_of_range(input: &str) {
+    let parsed = scim_filter_parser(input);
+    assert!(matches!(parsed, Err(Error::NumberOutOfRange(number)) if input.ends_with(&number)));
+}
+
 #[test_case("a eq \"test1\" and"; "and without content")]
+#[test_case("a eq 1.2.3"; "number with two dots")]
+#[test_case("a eq 01"; "number with leading zero")]
+#[test_case("a eq 1."; "number without fraction digits")]
+#[test_case("a eq .5"; "number without integer part")]
+#[test_case("a eq -"; "minus without digits")]
+#[test_case("a eq 1e"; "exponent without digits")]
 fn wrong_query1(input: &str) {
     let parsed = scim_filter_parser(input);
     assert!(parsed.is_err());
 }
+
+#[test_case("a eq \"\"", ""; "empty string")]
+#[test_case("a eq \"O\\\"Brien\"", "O\"Brien"; "escaped quote")]
+#[test_case("a eq \"back\\\\slash\"", "back\\slash"; "escaped backslash")]
+#[test_case("a eq \"a\\/b\"", "a/b"; "escaped solidus")]
+#[test_case("a eq \"line\\nbreak\\ttab\\r\\b\\f\"", "line\nbreak\ttab\r\u{08}\u{0C}"; "control characters escapes")]
+#[test_case("a eq \"Ren\\u00e9e\"", "Renée"; "unicode escape")]
+#[test_case("a eq \"\\u0041\\u0042\"", "AB"; "consecutive unicode escapes")]
+#[test_case("a eq \"\\ud83d\\ude00\"", "😀"; "unicode surrogate pair")]
+#[test_case("a eq \"Renée\"", "Renée"; "non ascii characters")]
+fn string_value(input: &str, expected: &str) {
+    let parsed = scim_filter_parser(input);
+    assert_eq!(attribute_expression("a", Equal, expected), parsed.unwrap());
+}
+
+#[test]
+fn string_value_without_escapes_is_borrowed() {
+    let parsed = scim_filter_parser("a eq \"test\"").unwrap();
+    assert!(matches!(
+        parsed,
+        Filter::AttrExp(Compare(_, _, CompValue::String(Cow::Borrowed("test"))))
+    ));
+}
+
+#[test_case("a eq \"unterminated"; "unterminated string")]
+#[test_case("a eq \"wrong \\x escape\""; "unknown escape")]
+#[test_case("a eq \"\\u12\""; "short unicode escape")]
+#[test_case("a eq \"\\ud83d\""; "lone surrogate")]
+#[test_case("a eq \"tab\tinside\""; "unescaped control character")]
+fn wrong_string_value(input: &str) {
+    let parsed = scim_filter_parser(input);
+    assert!(parsed.is_err());
+}
+
+#[test_case("a xx \"v\"", 2, 1, 3, vec!["presence operator", "comparison operator"]; "wrong operator")]
+#[test_case("a eq", 4, 1, 5, vec!["comparison value"]; "missing value")]
+#[test_case("a eq \"test\" and", 15, 1, 16, vec!["filter expression"]; "missing right side of logical expression")]
+#[test_case("(a pr or b pr", 13, 1, 14, vec!["closing parenthesis"]; "missing closing parenthesis")]
+#[test_case("emails[type eq \"work\"", 21, 1, 22, vec!["closing bracket"]; "missing closing bracket")]
+#[test_case("a eq \"test", 10, 1, 11, vec!["closing quote"]; "missing closing quote")]
+#[test_case("a pr foo", 5, 1, 6, vec!["logical operator"]; "trailing content")]
+#[test_case("a pr and\n b pr", 8, 1, 9, vec!["filter expression"]; "new line is not a space")]
+#[test_case("a pr and\tb xx 1", 11, 1, 12, vec!["presence operator", "comparison operator"]; "error after a tab")]
+#[test_case("émails eq \"x\"", 0, 1, 1, vec!["attribute path", "opening parenthesis"]; "wrong attribute name")]
+fn parse_error_position(
+    input: &str,
+    offset: usize,
+    line: usize,
+    column: usize,
+    expected: Vec<&'static str>,
+) {
+    let Err(Error::Parser(error)) = scim_filter_parser(input) else {
+        panic!("the filter {} should not be parsed", input);
+    };
+    assert_eq!(offset, error.offset());
+    assert_eq!(line, error.line());
+    assert_eq!(column, error.column());
+    assert_eq!(expected, error.expected());
+}
+
+#[test]
+fn parse_error_on_multiple_lines() {
+    let error = crate::ParseError::new("a pr\nand b xx 1", "xx 1", vec!["comparison operator"]);
+    assert_eq!(11, error.offset());
+    assert_eq!(2, error.line());
+    assert_eq!(7, error.column());
+}
+
+#[test]
+fn parse_error_display() {
+    let Err(error) = scim_filter_parser("a eq \"test\" and b xx \"v\"") else {
+        panic!("the filter should not be parsed");
+    };
+    assert_eq!(
+        "expected presence operator or comparison operator, found \"xx\" at line 1, column 19",
+        error.to_string()
+    );
+}
+
+#[test]
+fn parse_error_render() {
+    let Err(Error::Parser(error)) = scim_filter_parser("a eq \"test\" and") else {
+        panic!("the filter should not be parsed");
+    };
+    assert_eq!(
+        "a eq \"test\" and\n               ^ expected filter expression, found end of input at line 1, column 16",
+        error.render()
+    );
+}
+
+#[test]
+fn owned_filter_outlives_its_input() {
+    let input = String::from("a eq \"test\" and emails[type eq \"work\"] or not (b pr)");
+    let parsed = scim_filter_parser(&input).unwrap();
+    let expected = parsed.to_string();
+    let owned: Filter<'static> = parsed.clone().into_owned();
+    assert_eq!(parsed, owned);
+    drop(input);
+
+    assert_eq!(expected, owned.to_string());
+}
+
+#[test]
+fn owned_filter_can_be_sent_to_another_thread() {
+    fn assert_send_sync<T: Send + Sync + Clone>() {}
+    assert_send_sync::<Filter<'static>>();
+
+    let owned = scim_filter_parser("a eq \"test\"").unwrap().into_owned();
+    let printed = std::thread::spawn(move || owned.to_string())
+        .join()
+        .unwrap();
+    assert_eq!("a eq \"test\"", printed);
+}
+
+#[test_case("members", PatchPath::Attribute(AttrPath::new((None, AttrName::from_str("members"), None))); "attribute")]
+#[test_case("name.familyName", PatchPath::Attribute(AttrPath::new((None, AttrName::from_str("name"), Some(SubAttr::from_str("familyName"))))); "sub attribute")]
+#[test_case("urn:ietf:params:scim:schemas:core:2.0:User:userName", PatchPath::Attribute(AttrPath::new((Some("urn:ietf:params:scim:schemas:core:2.0:User".to_string()), AttrName::from_str("userName"), None))); "attribute with uri")]
+#[test_case("members[value eq \"2819c223\"]", PatchPath::ValuePath(member_value_path(), None); "value path")]
+#[test_case("members[value eq \"2819c223\"].display", PatchPath::ValuePath(member_value_path(), Some(SubAttr::from_str("display"))); "value path with sub attribute")]
+fn patch_path(input: &str, expected: PatchPath) {
+    assert_eq!(expected, patch_path_parser(input).unwrap());
+}
+
+fn member_value_path() -> ValuePathData<'static> {
+    ValuePathData::new((
+        AttrPath::new((None, AttrName::from_str("members"), None)),
+        ValFilter::AttrExp(Compare(
+            AttrPath::new((None, AttrName::from_str("value"), None)),
+            Equal,
+            CompValue::String("2819c223".into()),
+        )),
+    ))
+}
+
+#[test_case("members[value eq \"2819c223\"]display", 28, "end of path"; "sub attribute without dot")]
+#[test_case("members[value eq]", 16, "comparison value"; "invalid filter")]
+#[test_case("members eq \"2819c223\"", 8, "end of path"; "filter instead of path")]
+fn invalid_patch_path(input: &str, offset: usize, expected: &str) {
+    let Err(Error::Parser(error)) = patch_path_parser(input) else {
+        panic!("the path should not be parsed");
+    };
+    assert_eq!(offset, error.offset());
+    assert_eq!([expected], error.expected());
+}
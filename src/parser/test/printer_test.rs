@@ -0,0 +1,109 @@
+use rust_decimal_macros::dec;
+use test_case::test_case;
+
+use crate::parser::CompareOp::*;
+use crate::parser::LogExpOperator::*;
+use crate::parser::{
+    patch_path_parser, scim_filter_parser, AttrExpData, AttrName, AttrPath, CompValue, Filter,
+    LogExpData,
+};
+
+fn attribute_expression_pr(attribute: &str) -> Filter<'_> {
+    Filter::AttrExp(AttrExpData::Present(AttrPath::new((
+        None,
+        AttrName::from_str(attribute),
+        None,
+    ))))
+}
+
+#[test_case("a pr"; "present")]
+#[test_case("a eq \"test\""; "string comparison")]
+#[test_case("a ne true"; "true comparison")]
+#[test_case("a ne false"; "false comparison")]
+#[test_case("a eq null"; "null comparison")]
+#[test_case("a gt -2.5"; "number comparison")]
+#[test_case("a eq \"\""; "empty string")]
+#[test_case("a eq \"O\\\"Brien \\\\ \\n\""; "string with escapes")]
+#[test_case("a eq \"Renée\""; "string with non ascii characters")]
+#[test_case("name.familyName co \"O'Malley\""; "sub attribute")]
+#[test_case("urn:ietf:params:scim:schemas:core:2.0:User:userName sw \"J\""; "attribute with uri")]
+#[test_case("urn:acme-corp:scim:ext_1.0:User:costCenter eq \"4130\""; "attribute with custom extension uri")]
+#[test_case("a pr and b pr and c pr"; "and chain")]
+#[test_case("a pr or b pr and c pr"; "and has precedence over or")]
+#[test_case("a pr and b pr or c pr"; "and has precedence over or on the left")]
+#[test_case("a pr and (b pr or c pr)"; "parenthesis needed for precedence")]
+#[test_case("(a pr and b pr) or c pr"; "redundant parenthesis are kept")]
+#[test_case("not (a pr or b pr)"; "not expression")]
+#[test_case("emails[type eq \"work\" and value co \"@example.com\"]"; "value path")]
+#[test_case("emails[not (type eq \"work\")]"; "value path with not")]
+#[test_case("emails[type eq \"work\" and (primary eq true or value pr)]"; "value path with parenthesis")]
+#[test_case("userType eq \"Employee\" and emails[type eq \"work\"] or meta.lastModified gt \"2011-05-13T04:42:34Z\""; "complex filter")]
+fn canonical_filter_is_printed_unchanged(input: &str) {
+    let parsed = scim_filter_parser(input).unwrap();
+    assert_eq!(input, parsed.to_string());
+}
+
+#[test_case("members"; "attribute")]
+#[test_case("name.familyName"; "sub attribute")]
+#[test_case("urn:ietf:params:scim:schemas:extension:enterprise:2.0:User:manager"; "attribute with uri")]
+#[test_case("emails[type eq \"work\"]"; "value path")]
+#[test_case("members[value eq \"2819c223\"].display"; "value path with sub attribute")]
+fn canonical_patch_path_is_printed_unchanged(input: &str) {
+    let parsed = patch_path_parser(input).unwrap();
+    assert_eq!(input, parsed.to_string());
+}
+
+#[test_case("a   PR", "a pr"; "extra spaces and uppercase operator")]
+#[test_case("a EQ \"test\" AND b Ne 1.50", "a eq \"test\" and b ne 1.5"; "uppercase logical operator")]
+#[test_case("a eq 1e3", "a eq 1000"; "number with exponent")]
+#[test_case("a eq \"\\u0041\\/\"", "a eq \"A/\""; "unnecessary escapes")]
+#[test_case("not(a pr)", "not (a pr)"; "not without space")]
+fn filter_is_printed_in_canonical_form(input: &str, expected: &str) {
+    let parsed = scim_filter_parser(input).unwrap();
+    let printed = parsed.to_string();
+    assert_eq!(expected, printed);
+    assert_eq!(parsed, scim_filter_parser(&printed).unwrap());
+}
+
+#[test]
+fn or_inside_and_is_parenthesized() {
+    let filter = Filter::LogExp(LogExpData::new((
+        Filter::LogExp(LogExpData::new((
+            attribute_expression_pr("a"),
+            Or,
+            attribute_expression_pr("b"),
+        ))),
+        And,
+        attribute_expression_pr("c"),
+    )));
+    assert_eq!("(a pr or b pr) and c pr", filter.to_string());
+}
+
+#[test]
+fn and_inside_or_is_not_parenthesized() {
+    let filter = Filter::LogExp(LogExpData::new((
+        attribute_expression_pr("a"),
+        Or,
+        Filter::LogExp(LogExpData::new((
+            attribute_expression_pr("b"),
+            And,
+            attribute_expression_pr("c"),
+        ))),
+    )));
+    assert_eq!("a pr or b pr and c pr", filter.to_string());
+}
+
+#[test]
+fn comp_values() {
+    let attr_path = || AttrPath::new((None, AttrName::from_str("a"), None));
+    let compare =
+        |comp_value| Filter::AttrExp(AttrExpData::Compare(attr_path(), Equal, comp_value));
+    assert_eq!(
+        "a eq 10.25",
+        compare(CompValue::Number(dec!(10.250))).to_string()
+    );
+    assert_eq!(
+        "a eq \"tab\\t\\u0001\"",
+        compare(CompValue::String("tab\t\u{1}".into())).to_string()
+    );
+}
@@ -0,0 +1,469 @@
+//! The operations of a PATCH request, RFC 7644 section 3.5.2, applied to json resources.
+
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value as JsonValue};
+
+use crate::error::{Error, PatchError};
+use crate::matcher::is_core_schema;
+use crate::parser::{patch_path_parser, AttrPath, PatchPath, ValuePathData};
+use crate::resource::CaseInsensitiveGet;
+use crate::MatchOptions;
+
+#[cfg(test)]
+#[path = "test/patch_test.rs"]
+mod patch_test;
+
+/// The `op` of a PATCH operation
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PatchOperation {
+    Add,
+    Remove,
+    Replace,
+}
+
+impl PatchOperation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PatchOperation::Add => "add",
+            PatchOperation::Remove => "remove",
+            PatchOperation::Replace => "replace",
+        }
+    }
+}
+
+impl FromStr for PatchOperation {
+    type Err = PatchError;
+
+    /// the operation is case-insensitive, since some clients send `Add` or `Replace`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [
+            PatchOperation::Add,
+            PatchOperation::Remove,
+            PatchOperation::Replace,
+        ]
+        .into_iter()
+        .find(|op| op.as_str().eq_ignore_ascii_case(s))
+        .ok_or_else(|| PatchError::InvalidOperation(s.to_string()))
+    }
+}
+
+impl Display for PatchOperation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// An operation of a PATCH request.
+///
+/// It deserializes from the json of the `Operations` of a `PatchOp` request, and it's applied to a
+/// json resource following the rules of RFC 7644 section 3.5.2.
+///
+/// ```
+/// use scim_filter::PatchOp;
+///
+/// let mut group = serde_json::json!({
+///     "displayName": "Tour Guides",
+///     "members": [
+///         {"value": "2819c223", "display": "Babs Jensen"},
+///         {"value": "902c246b", "display": "Mandy Pepperidge"}
+///     ]
+/// });
+/// let operation: PatchOp = serde_json::from_value(serde_json::json!({
+///     "op": "remove",
+///     "path": "members[value eq \"2819c223\"]"
+/// }))
+/// .unwrap();
+/// operation.apply(&mut group, &Default::default()).unwrap();
+/// assert_eq!(
+///     serde_json::json!([{"value": "902c246b", "display": "Mandy Pepperidge"}]),
+///     group["members"]
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "RawPatchOp", into = "RawPatchOp")]
+pub struct PatchOp {
+    pub op: PatchOperation,
+    /// the target of the operation, `None` is the resource itself
+    pub path: Option<PatchPath<'static>>,
+    pub value: Option<JsonValue>,
+}
+
+impl PatchOp {
+    /// creates an operation, parsing its path
+    pub fn new(
+        op: PatchOperation,
+        path: Option<&str>,
+        value: Option<JsonValue>,
+    ) -> Result<Self, Error> {
+        let path = path
+            .map(|path| {
+                patch_path_parser(path)
+                    .map(PatchPath::into_owned)
+                    .map_err(|error| PatchError::InvalidPath(path.to_string(), error.to_string()))
+            })
+            .transpose()?;
+        Ok(Self { op, path, value })
+    }
+
+    pub fn add(path: Option<&str>, value: JsonValue) -> Result<Self, Error> {
+        Self::new(PatchOperation::Add, path, Some(value))
+    }
+
+    pub fn remove(path: &str) -> Result<Self, Error> {
+        Self::new(PatchOperation::Remove, Some(path), None)
+    }
+
+    pub fn replace(path: Option<&str>, value: JsonValue) -> Result<Self, Error> {
+        Self::new(PatchOperation::Replace, path, Some(value))
+    }
+
+    /// applies the operation to a json resource.
+    ///
+    /// The filters in the path are matched with the given options. When the operation fails the
+    /// resource is left unchanged.
+    pub fn apply(&self, resource: &mut JsonValue, options: &MatchOptions) -> Result<(), Error> {
+        let change = match (self.op, &self.value) {
+            (PatchOperation::Remove, _) => Change::Remove,
+            (PatchOperation::Add, Some(value)) => Change::Add(value),
+            (PatchOperation::Replace, Some(value)) => Change::Replace(value),
+            (op, None) => return Err(PatchError::MissingValue(op).into()),
+        };
+        let mut patched = resource.clone();
+        match &self.path {
+            None => apply_to_resource(&mut patched, change)?,
+            Some(PatchPath::Attribute(attr_path)) => {
+                apply_to_attribute(&mut patched, attr_path, change)?
+            }
+            Some(PatchPath::ValuePath(value_path_data, sub_attr)) => apply_to_values(
+                &mut patched,
+                value_path_data,
+                sub_attr.as_deref(),
+                change,
+                options,
+            )?,
+        }
+        *resource = patched;
+        Ok(())
+    }
+}
+
+/// The json of an operation, with the path not yet parsed
+#[derive(Serialize, Deserialize)]
+struct RawPatchOp {
+    op: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    value: Option<JsonValue>,
+}
+
+impl TryFrom<RawPatchOp> for PatchOp {
+    type Error = Error;
+
+    fn try_from(raw: RawPatchOp) -> Result<Self, Self::Error> {
+        Self::new(raw.op.parse()?, raw.path.as_deref(), raw.value)
+    }
+}
+
+impl From<PatchOp> for RawPatchOp {
+    fn from(patch_op: PatchOp) -> Self {
+        Self {
+            op: patch_op.op.as_str().to_string(),
+            path: patch_op.path.as_ref().map(ToString::to_string),
+            value: patch_op.value,
+        }
+    }
+}
+
+/// What an operation does to the attributes it targets
+#[derive(Clone, Copy)]
+enum Change<'v> {
+    Add(&'v JsonValue),
+    Remove,
+    Replace(&'v JsonValue),
+}
+
+impl<'v> Change<'v> {
+    /// the same change, with another value
+    fn with_value(self, value: &'v JsonValue) -> Self {
+        match self {
+            Change::Add(_) => Change::Add(value),
+            Change::Remove => Change::Remove,
+            Change::Replace(_) => Change::Replace(value),
+        }
+    }
+
+    fn apply_to(self, object: &mut Map<String, JsonValue>, name: &str) {
+        match self {
+            Change::Add(value) => add_attribute(object, name, value),
+            Change::Remove => {
+                if let Some(key) = existing_key(object, name) {
+                    object.remove(&key);
+                }
+            }
+            Change::Replace(value) => replace_attribute(object, name, value),
+        }
+    }
+}
+
+/// without a path, the value holds the attributes to add or to replace
+fn apply_to_resource(resource: &mut JsonValue, change: Change) -> Result<(), Error> {
+    let attributes = match change {
+        Change::Remove => return Err(PatchError::MissingPath.into()),
+        Change::Add(JsonValue::Object(attributes))
+        | Change::Replace(JsonValue::Object(attributes)) => attributes,
+        Change::Add(value) | Change::Replace(value) => {
+            return Err(PatchError::InvalidValue(format!(
+                "{value} is not an object with the attributes of the resource"
+            ))
+            .into())
+        }
+    };
+    let resource = resource.as_object_mut().ok_or(Error::InvalidResource)?;
+    for (name, value) in attributes {
+        change.with_value(value).apply_to(resource, name);
+    }
+    Ok(())
+}
+
+fn apply_to_attribute(
+    resource: &mut JsonValue,
+    attr_path: &AttrPath,
+    change: Change,
+) -> Result<(), Error> {
+    let create = !matches!(change, Change::Remove);
+    // removing an attribute that doesn't exist leaves the resource as it is
+    let Some(parent) = attribute_parent(resource, attr_path, create)? else {
+        return Ok(());
+    };
+    let Some(sub_attr) = attr_path.sub_attr() else {
+        change.apply_to(parent, attr_path.attr_name());
+        return Ok(());
+    };
+    let key = match existing_key(parent, attr_path.attr_name()) {
+        Some(key) => key,
+        None if !create => return Ok(()),
+        None => {
+            let key = attr_path.attr_name().to_string();
+            parent.insert(key.clone(), JsonValue::Object(Map::new()));
+            key
+        }
+    };
+    match &mut parent[&key] {
+        JsonValue::Object(complex) => change.apply_to(complex, sub_attr),
+        // the sub-attribute of every value of a multi-valued attribute
+        JsonValue::Array(values) => values
+            .iter_mut()
+            .filter_map(JsonValue::as_object_mut)
+            .for_each(|complex| change.apply_to(complex, sub_attr)),
+        JsonValue::Null if !create => {}
+        value @ JsonValue::Null => {
+            let mut complex = Map::new();
+            change.apply_to(&mut complex, sub_attr);
+            *value = JsonValue::Object(complex);
+        }
+        _ => {
+            return Err(PatchError::InvalidPath(
+                attr_path.to_string(),
+                "the attribute has no sub-attributes".to_string(),
+            )
+            .into())
+        }
+    }
+    Ok(())
+}
+
+/// applies the change to the values selected by the filter, or to their sub-attribute
+fn apply_to_values(
+    resource: &mut JsonValue,
+    value_path_data: &ValuePathData,
+    sub_attr: Option<&str>,
+    change: Change,
+    options: &MatchOptions,
+) -> Result<(), PatchError> {
+    let path = match sub_attr {
+        Some(sub_attr) => format!("{value_path_data}.{sub_attr}"),
+        None => value_path_data.to_string(),
+    };
+    let no_target = || PatchError::NoTarget(path.clone());
+    let (pointer, _) = value_path_data
+        .attr_path()
+        .json_pointer(resource)
+        .ok_or_else(no_target)?;
+    let indices = value_path_data
+        .matching_elements(&*resource, options)
+        .map_err(|error| PatchError::InvalidPath(path.clone(), error.to_string()))?;
+    if indices.is_empty() {
+        return Err(no_target());
+    }
+    let target = resource
+        .pointer_mut(&pointer)
+        .expect("the pointer has been resolved on the same resource");
+
+    if let (Change::Remove, None) = (change, sub_attr) {
+        // the attribute is unassigned when none of its values remains
+        let is_empty = match target {
+            JsonValue::Array(values) => {
+                let mut index = 0;
+                values.retain(|_| {
+                    index += 1;
+                    !indices.contains(&(index - 1))
+                });
+                values.is_empty()
+            }
+            _ => true,
+        };
+        if is_empty {
+            remove_pointer(resource, &pointer);
+        }
+        return Ok(());
+    }
+
+    let selected: Vec<&mut JsonValue> = match target {
+        JsonValue::Array(values) => values
+            .iter_mut()
+            .enumerate()
+            .filter(|(index, _)| indices.contains(index))
+            .map(|(_, value)| value)
+            .collect(),
+        target => vec![target],
+    };
+    for value in selected {
+        match (change, sub_attr, value) {
+            (change, Some(sub_attr), JsonValue::Object(complex)) => {
+                change.apply_to(complex, sub_attr)
+            }
+            (_, Some(_), _) => {
+                return Err(PatchError::InvalidPath(
+                    path,
+                    "the selected values have no sub-attributes".to_string(),
+                ))
+            }
+            (Change::Add(JsonValue::Object(sub_attributes)), None, JsonValue::Object(complex)) => {
+                for (name, sub_value) in sub_attributes {
+                    add_attribute(complex, name, sub_value);
+                }
+            }
+            (Change::Add(new_value), None, _) => {
+                return Err(PatchError::InvalidValue(format!(
+                    "{new_value} can't be added to the values selected by {path}"
+                )))
+            }
+            (Change::Replace(new_value), None, value) => *value = new_value.clone(),
+            (Change::Remove, None, _) => unreachable!("the values have been removed"),
+        }
+    }
+    Ok(())
+}
+
+/// The object holding the attribute: the resource, or the object of its schema extension.
+///
+/// A missing extension object is created if asked, otherwise there is no parent.
+fn attribute_parent<'r>(
+    resource: &'r mut JsonValue,
+    attr_path: &AttrPath,
+    create: bool,
+) -> Result<Option<&'r mut Map<String, JsonValue>>, Error> {
+    let resource = resource.as_object_mut().ok_or(Error::InvalidResource)?;
+    let Some(uri) = attr_path.uri() else {
+        return Ok(Some(resource));
+    };
+    let key = existing_key(resource, uri);
+    match key {
+        Some(key) if resource[&key].is_object() => Ok(resource[&key].as_object_mut()),
+        _ if is_core_schema(uri) => Ok(Some(resource)),
+        _ if create => {
+            add_schema(resource, uri);
+            let key = key.unwrap_or_else(|| uri.clone());
+            resource.insert(key.clone(), JsonValue::Object(Map::new()));
+            Ok(resource[&key].as_object_mut())
+        }
+        _ => Ok(None),
+    }
+}
+
+/// lists an extension among the `schemas` of the resource
+fn add_schema(resource: &mut Map<String, JsonValue>, uri: &str) {
+    let Some(JsonValue::Array(schemas)) =
+        existing_key(resource, "schemas").and_then(|key| resource.get_mut(&key))
+    else {
+        return;
+    };
+    if !schemas
+        .iter()
+        .filter_map(JsonValue::as_str)
+        .any(|schema| schema.eq_ignore_ascii_case(uri))
+    {
+        schemas.push(JsonValue::String(uri.to_string()));
+    }
+}
+
+/// the key of the attribute as it's written in the object
+fn existing_key(object: &Map<String, JsonValue>, name: &str) -> Option<String> {
+    object
+        .get_key_value_insensitive(name)
+        .map(|(key, _)| key.clone())
+}
+
+/// Adds a value to the attribute: the values are appended to a multi-valued attribute, unless
+/// it already has them, the sub-attributes are added to a complex attribute, and the value of
+/// a single-valued attribute is replaced.
+fn add_attribute(object: &mut Map<String, JsonValue>, name: &str, value: &JsonValue) {
+    let existing = existing_key(object, name).and_then(|key| object.get_mut(&key));
+    match (existing, value) {
+        (Some(JsonValue::Array(values)), value) => {
+            let new_values = match value {
+                JsonValue::Array(new_values) => new_values.as_slice(),
+                value => std::slice::from_ref(value),
+            };
+            for new_value in new_values {
+                if !values.contains(new_value) {
+                    values.push(new_value.clone());
+                }
+            }
+        }
+        (Some(JsonValue::Object(complex)), JsonValue::Object(sub_attributes)) => {
+            for (name, sub_value) in sub_attributes {
+                add_attribute(complex, name, sub_value);
+            }
+        }
+        (Some(existing), value) => *existing = value.clone(),
+        (None, value) => {
+            object.insert(name.to_string(), value.clone());
+        }
+    }
+}
+
+/// Replaces the value of the attribute. The sub-attributes given for a complex attribute replace
+/// its sub-attributes, while the ones not given are left unchanged.
+fn replace_attribute(object: &mut Map<String, JsonValue>, name: &str, value: &JsonValue) {
+    let existing = existing_key(object, name).and_then(|key| object.get_mut(&key));
+    match (existing, value) {
+        (Some(JsonValue::Object(complex)), JsonValue::Object(sub_attributes)) => {
+            for (name, sub_value) in sub_attributes {
+                replace_attribute(complex, name, sub_value);
+            }
+        }
+        (Some(existing), value) => *existing = value.clone(),
+        (None, value) => {
+            object.insert(name.to_string(), value.clone());
+        }
+    }
+}
+
+/// removes the attribute addressed by a JSON pointer
+fn remove_pointer(resource: &mut JsonValue, pointer: &str) {
+    let Some((parent, key)) = pointer.rsplit_once('/') else {
+        return;
+    };
+    let key = key.replace("~1", "/").replace("~0", "~");
+    if let Some(parent) = resource
+        .pointer_mut(parent)
+        .and_then(JsonValue::as_object_mut)
+    {
+        parent.remove(&key);
+    }
+}
@@ -0,0 +1,145 @@
+use serde::Serialize;
+
+use crate::error::Error;
+use crate::sort::SortKey;
+use crate::{CompiledFilter, ListResponse, ScimResource, Sort};
+
+#[cfg(test)]
+#[path = "test/query_test.rs"]
+mod query_test;
+
+/// A query on a list of resources, with the `filter`, `sortBy`, `sortOrder`, `startIndex` and
+/// `count` parameters of RFC 7644 section 3.4.2.
+///
+/// The resources matching the filter are sorted, and the page starting at `startIndex` is returned
+/// in a [`ListResponse`]:
+///
+/// ```
+/// use scim_filter::{CompiledFilter, Query, Sort};
+///
+/// let users = vec![
+///     serde_json::json!({"userName": "jsmith", "active": true}),
+///     serde_json::json!({"userName": "bjensen", "active": true}),
+///     serde_json::json!({"userName": "jdoe", "active": false}),
+/// ];
+/// let query = Query::new()
+///     .with_filter(CompiledFilter::new("active eq true").unwrap())
+///     .with_sort(Sort::new("userName").unwrap())
+///     .with_count(1);
+/// let page = query.execute(users).unwrap();
+/// assert_eq!(2, page.total_results);
+/// assert_eq!("bjensen", page.resources[0]["userName"]);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Query {
+    filter: Option<CompiledFilter>,
+    sort: Option<Sort>,
+    start_index: Option<usize>,
+    count: Option<usize>,
+}
+
+impl Query {
+    /// a query returning all the resources, in their order
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_filter(mut self, filter: CompiledFilter) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    pub fn with_sort(mut self, sort: Sort) -> Self {
+        self.sort = Some(sort);
+        self
+    }
+
+    /// the 1-based index of the first resource to return, a value lower than 1 is 1
+    pub fn with_start_index(mut self, start_index: i64) -> Self {
+        self.start_index = Some(start_index.try_into().unwrap_or(1).max(1));
+        self
+    }
+
+    /// the maximum number of resources to return, a negative value is 0. With a count of 0 only
+    /// the number of matching resources is returned.
+    pub fn with_count(mut self, count: i64) -> Self {
+        self.count = Some(count.try_into().unwrap_or(0));
+        self
+    }
+
+    pub fn filter(&self) -> Option<&CompiledFilter> {
+        self.filter.as_ref()
+    }
+
+    pub fn sort(&self) -> Option<&Sort> {
+        self.sort.as_ref()
+    }
+
+    pub fn start_index(&self) -> usize {
+        self.start_index.unwrap_or(1)
+    }
+
+    /// the count, `None` returns all the resources from the start index
+    pub fn count(&self) -> Option<usize> {
+        self.count
+    }
+
+    /// runs the query on the resources, each resource is serialized to json once, to be matched
+    /// and sorted
+    pub fn execute<T: Serialize>(
+        &self,
+        resources: impl IntoIterator<Item = T>,
+    ) -> Result<ListResponse<T>, Error> {
+        let matched = resources
+            .into_iter()
+            .try_fold(vec![], |mut acc, resource| {
+                let value = serde_json::to_value(&resource)?;
+                if self.matches(&value)? {
+                    acc.push((self.sort_key(&value), resource));
+                }
+                Ok::<_, Error>(acc)
+            })?;
+        Ok(self.page(matched))
+    }
+
+    /// runs the query on the resources, reading their attributes through [`ScimResource`]
+    pub fn execute_resources<R: ScimResource>(
+        &self,
+        resources: impl IntoIterator<Item = R>,
+    ) -> Result<ListResponse<R>, Error> {
+        let matched = resources
+            .into_iter()
+            .try_fold(vec![], |mut acc, resource| {
+                if self.matches(&resource)? {
+                    acc.push((self.sort_key(&resource), resource));
+                }
+                Ok::<_, Error>(acc)
+            })?;
+        Ok(self.page(matched))
+    }
+
+    fn matches<R: ScimResource + ?Sized>(&self, resource: &R) -> Result<bool, Error> {
+        self.filter
+            .as_ref()
+            .map_or(Ok(true), |filter| filter.matches_resource(resource))
+    }
+
+    fn sort_key<R: ScimResource + ?Sized>(&self, resource: &R) -> Option<SortKey> {
+        self.sort.as_ref().map(|sort| sort.sort_key(resource))
+    }
+
+    /// sorts the matching resources and returns the requested page of them. A start index past
+    /// the last resource is an empty page.
+    fn page<T>(&self, mut matched: Vec<(Option<SortKey>, T)>) -> ListResponse<T> {
+        // without a sort all the keys are equal, and the resources keep their order
+        matched.sort_by(|(left, _), (right, _)| left.cmp(right));
+        let total_results = matched.len();
+        let resources = matched
+            .into_iter()
+            .skip(self.start_index() - 1)
+            .take(self.count.unwrap_or(usize::MAX))
+            .map(|(_, resource)| resource)
+            .collect();
+        ListResponse::new(resources, total_results, self.start_index())
+    }
+}
@@ -0,0 +1,336 @@
+use std::borrow::Cow;
+use std::fmt::{Debug, Display, Formatter};
+use std::rc::Rc;
+use std::sync::Arc;
+
+use chrono::{DateTime, FixedOffset, TimeZone};
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+use serde_json::{Map, Number, Value as JsonValue};
+
+#[cfg(test)]
+#[path = "test/resource_test.rs"]
+mod resource_test;
+
+/// A resource whose attributes can be read by the matcher.
+///
+/// It's implemented for [`serde_json::Value`], and it can be derived for structs with the `derive`
+/// feature, so that typed resources can be matched without serializing them to json first:
+///
+/// ```ignore
+/// #[derive(ScimResource)]
+/// #[scim(rename_all = "camelCase")]
+/// struct User {
+///     user_name: String,
+///     #[scim(rename = "emails")]
+///     email_addresses: Vec<Email>,
+///     #[scim(skip)]
+///     password: String,
+/// }
+/// ```
+pub trait ScimResource {
+    /// returns the value of the attribute, or [`AttrValue::Null`] if the resource doesn't have it.
+    ///
+    /// Attribute names are case-insensitive, as specified by RFC 7643.
+    fn get_attr(&self, name: &str) -> AttrValue<'_>;
+}
+
+/// The value of an attribute, borrowed from the resource it's been read from.
+#[derive(Clone)]
+pub enum AttrValue<'a> {
+    Null,
+    Bool(bool),
+    Number(Decimal),
+    String(Cow<'a, str>),
+    DateTime(DateTime<FixedOffset>),
+    /// a complex attribute, with its own sub-attributes
+    Complex(&'a dyn ScimResource),
+    /// a multi-valued attribute
+    Multi(Vec<AttrValue<'a>>),
+}
+
+impl<'a> AttrValue<'a> {
+    pub fn is_null(&self) -> bool {
+        matches!(self, AttrValue::Null)
+    }
+
+    /// reads a sub-attribute.
+    ///
+    /// On a multi-valued attribute the sub-attribute is read from every complex value,
+    /// and the values that have it are collected.
+    pub fn into_sub_attr(self, name: &str) -> AttrValue<'a> {
+        match self {
+            AttrValue::Complex(resource) => resource.get_attr(name),
+            AttrValue::Multi(values) => AttrValue::Multi(
+                values
+                    .into_iter()
+                    .filter_map(|value| match value {
+                        AttrValue::Complex(resource) => Some(resource.get_attr(name)),
+                        _ => None,
+                    })
+                    .filter(|value| !value.is_null())
+                    .collect(),
+            ),
+            _ => AttrValue::Null,
+        }
+    }
+}
+
+/// a complex or multi-valued attribute can be the resource of a value path filter
+impl<'a> ScimResource for AttrValue<'a> {
+    fn get_attr(&self, name: &str) -> AttrValue<'_> {
+        self.clone().into_sub_attr(name)
+    }
+}
+
+impl<'a> Debug for AttrValue<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AttrValue::Null => f.write_str("Null"),
+            AttrValue::Bool(value) => f.debug_tuple("Bool").field(value).finish(),
+            AttrValue::Number(value) => f.debug_tuple("Number").field(value).finish(),
+            AttrValue::String(value) => f.debug_tuple("String").field(value).finish(),
+            AttrValue::DateTime(value) => f.debug_tuple("DateTime").field(value).finish(),
+            AttrValue::Complex(_) => f.write_str("Complex(..)"),
+            AttrValue::Multi(values) => f.debug_tuple("Multi").field(values).finish(),
+        }
+    }
+}
+
+impl<'a> PartialEq for AttrValue<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (AttrValue::Null, AttrValue::Null) => true,
+            (AttrValue::Bool(a), AttrValue::Bool(b)) => a == b,
+            (AttrValue::Number(a), AttrValue::Number(b)) => a == b,
+            (AttrValue::String(a), AttrValue::String(b)) => a == b,
+            (AttrValue::DateTime(a), AttrValue::DateTime(b)) => a == b,
+            // complex values are equal only if they are the same resource
+            (AttrValue::Complex(a), AttrValue::Complex(b)) => std::ptr::addr_eq(*a, *b),
+            (AttrValue::Multi(a), AttrValue::Multi(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl<'a> Display for AttrValue<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AttrValue::Null => f.write_str("null"),
+            AttrValue::Bool(value) => write!(f, "{}", value),
+            AttrValue::Number(value) => write!(f, "{}", value),
+            AttrValue::String(value) => write!(f, "\"{}\"", value),
+            AttrValue::DateTime(value) => write!(f, "\"{}\"", value.to_rfc3339()),
+            AttrValue::Complex(_) => f.write_str("{complex value}"),
+            AttrValue::Multi(values) => {
+                f.write_str("[")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                f.write_str("]")
+            }
+        }
+    }
+}
+
+/// Conversion of a field to the value of an attribute, used by the `ScimResource` derive.
+pub trait AsAttrValue {
+    fn as_attr_value(&self) -> AttrValue<'_>;
+}
+
+impl AsAttrValue for str {
+    fn as_attr_value(&self) -> AttrValue<'_> {
+        AttrValue::String(Cow::Borrowed(self))
+    }
+}
+
+impl AsAttrValue for String {
+    fn as_attr_value(&self) -> AttrValue<'_> {
+        AttrValue::String(Cow::Borrowed(self))
+    }
+}
+
+impl<'b> AsAttrValue for Cow<'b, str> {
+    fn as_attr_value(&self) -> AttrValue<'_> {
+        AttrValue::String(Cow::Borrowed(self))
+    }
+}
+
+impl AsAttrValue for bool {
+    fn as_attr_value(&self) -> AttrValue<'_> {
+        AttrValue::Bool(*self)
+    }
+}
+
+macro_rules! as_attr_value_for_integers {
+    ($($integer:ty),*) => {
+        $(
+            impl AsAttrValue for $integer {
+                fn as_attr_value(&self) -> AttrValue<'_> {
+                    AttrValue::Number(Decimal::from(*self))
+                }
+            }
+        )*
+    };
+}
+
+as_attr_value_for_integers!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+impl AsAttrValue for f32 {
+    fn as_attr_value(&self) -> AttrValue<'_> {
+        float_to_attr_value(Decimal::from_f32(*self), self)
+    }
+}
+
+impl AsAttrValue for f64 {
+    fn as_attr_value(&self) -> AttrValue<'_> {
+        float_to_attr_value(Decimal::from_f64(*self), self)
+    }
+}
+
+/// numbers that can't be represented as a [`Decimal`] are kept as text,
+/// so that comparing them fails like comparing any other malformed number
+fn float_to_attr_value(decimal: Option<Decimal>, number: &impl ToString) -> AttrValue<'static> {
+    decimal
+        .map(AttrValue::Number)
+        .unwrap_or_else(|| AttrValue::String(Cow::Owned(number.to_string())))
+}
+
+impl AsAttrValue for Decimal {
+    fn as_attr_value(&self) -> AttrValue<'_> {
+        AttrValue::Number(*self)
+    }
+}
+
+impl<Tz: TimeZone> AsAttrValue for DateTime<Tz> {
+    fn as_attr_value(&self) -> AttrValue<'_> {
+        AttrValue::DateTime(self.fixed_offset())
+    }
+}
+
+impl<T: AsAttrValue> AsAttrValue for Option<T> {
+    fn as_attr_value(&self) -> AttrValue<'_> {
+        match self {
+            Some(value) => value.as_attr_value(),
+            None => AttrValue::Null,
+        }
+    }
+}
+
+impl<T: AsAttrValue> AsAttrValue for [T] {
+    fn as_attr_value(&self) -> AttrValue<'_> {
+        AttrValue::Multi(self.iter().map(AsAttrValue::as_attr_value).collect())
+    }
+}
+
+impl<T: AsAttrValue> AsAttrValue for Vec<T> {
+    fn as_attr_value(&self) -> AttrValue<'_> {
+        self.as_slice().as_attr_value()
+    }
+}
+
+impl<T: AsAttrValue + ?Sized> AsAttrValue for &T {
+    fn as_attr_value(&self) -> AttrValue<'_> {
+        (**self).as_attr_value()
+    }
+}
+
+impl<T: AsAttrValue + ?Sized> AsAttrValue for Box<T> {
+    fn as_attr_value(&self) -> AttrValue<'_> {
+        (**self).as_attr_value()
+    }
+}
+
+impl<T: AsAttrValue + ?Sized> AsAttrValue for Rc<T> {
+    fn as_attr_value(&self) -> AttrValue<'_> {
+        (**self).as_attr_value()
+    }
+}
+
+impl<T: AsAttrValue + ?Sized> AsAttrValue for Arc<T> {
+    fn as_attr_value(&self) -> AttrValue<'_> {
+        (**self).as_attr_value()
+    }
+}
+
+impl AsAttrValue for JsonValue {
+    fn as_attr_value(&self) -> AttrValue<'_> {
+        match self {
+            JsonValue::Null => AttrValue::Null,
+            JsonValue::Bool(value) => AttrValue::Bool(*value),
+            JsonValue::Number(value) => number_to_attr_value(value),
+            JsonValue::String(value) => AttrValue::String(Cow::Borrowed(value)),
+            JsonValue::Array(values) => values.as_attr_value(),
+            JsonValue::Object(object) => AttrValue::Complex(object),
+        }
+    }
+}
+
+fn number_to_attr_value(number: &Number) -> AttrValue<'static> {
+    if let Some(value_i64) = number.as_i64() {
+        return AttrValue::Number(Decimal::from(value_i64));
+    }
+    if let Some(value_u64) = number.as_u64() {
+        return AttrValue::Number(Decimal::from(value_u64));
+    }
+    float_to_attr_value(number.as_f64().and_then(Decimal::from_f64), number)
+}
+
+impl ScimResource for JsonValue {
+    fn get_attr(&self, name: &str) -> AttrValue<'_> {
+        match self {
+            JsonValue::Object(object) => object.get_attr(name),
+            // the attribute is read from every object of the array
+            JsonValue::Array(values) => AttrValue::Multi(
+                values
+                    .iter()
+                    .filter_map(|value| value.as_object()?.get_insensitive(name))
+                    .map(AsAttrValue::as_attr_value)
+                    .collect(),
+            ),
+            _ => AttrValue::Null,
+        }
+    }
+}
+
+impl ScimResource for Map<String, JsonValue> {
+    fn get_attr(&self, name: &str) -> AttrValue<'_> {
+        self.get_insensitive(name)
+            .map(AsAttrValue::as_attr_value)
+            .unwrap_or(AttrValue::Null)
+    }
+}
+
+impl<T: ScimResource + ?Sized> ScimResource for &T {
+    fn get_attr(&self, name: &str) -> AttrValue<'_> {
+        (**self).get_attr(name)
+    }
+}
+
+pub(crate) trait CaseInsensitiveGet {
+    fn get_insensitive(&self, key: &str) -> Option<&JsonValue> {
+        self.get_key_value_insensitive(key).map(|(_, value)| value)
+    }
+
+    /// returns the key as it's written in the object, together with its value
+    fn get_key_value_insensitive(&self, key: &str) -> Option<(&String, &JsonValue)>;
+}
+
+impl CaseInsensitiveGet for Map<String, JsonValue> {
+    fn get_key_value_insensitive(&self, key: &str) -> Option<(&String, &JsonValue)> {
+        if let Some(key_value) = self.get_key_value(key) {
+            return Some(key_value);
+        }
+        // compares the lowercase chars one by one, to avoid allocating a lowercase copy of every key
+        let key: Vec<char> = key.chars().flat_map(char::to_lowercase).collect();
+        self.iter().find(|(value_key, _)| {
+            value_key
+                .chars()
+                .flat_map(char::to_lowercase)
+                .eq(key.iter().copied())
+        })
+    }
+}
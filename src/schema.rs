@@ -0,0 +1,224 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+
+use crate::error::Error;
+use crate::parser::AttrPath;
+
+#[cfg(test)]
+#[path = "test/schema_test.rs"]
+mod schema_test;
+
+/// The definition of a resource schema, as described by RFC 7643 section 7.
+///
+/// It can be loaded from the json returned by the `/Schemas` endpoint of a SCIM service provider.
+/// When a filter is matched with a schema, the declared type of every attribute decides how its
+/// values are compared, instead of guessing it from the values themselves.
+///
+/// ```
+/// use scim_filter::{CompiledFilter, Schema};
+///
+/// let schema: Schema = r#"{
+///     "id": "urn:ietf:params:scim:schemas:core:2.0:User",
+///     "attributes": [{"name": "userName", "type": "string"}]
+/// }"#.parse().unwrap();
+/// let filter = CompiledFilter::new("userName eq \"42\"").unwrap().with_schema(schema);
+/// assert!(filter.matches_value(&serde_json::json!({"userName": "42"})).unwrap());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Schema {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub attributes: Vec<AttributeDefinition>,
+    /// the schema extensions of the resource, their attributes are addressed with the extension URI
+    #[serde(skip)]
+    pub extensions: Vec<Schema>,
+}
+
+impl Schema {
+    pub fn new(id: impl Into<String>, attributes: Vec<AttributeDefinition>) -> Self {
+        Self {
+            id: id.into(),
+            attributes,
+            ..Self::default()
+        }
+    }
+
+    /// adds a schema extension, like `urn:ietf:params:scim:schemas:extension:enterprise:2.0:User`.
+    ///
+    /// As in RFC 7644 section 3.10, an attribute without a schema URI is an attribute of the core
+    /// schema: the attributes of an extension are only found with their full URI, like
+    /// `urn:ietf:params:scim:schemas:extension:enterprise:2.0:User:manager.value`, and not as
+    /// `manager.value`.
+    pub fn with_extension(mut self, extension: Schema) -> Self {
+        self.extensions.push(extension);
+        self
+    }
+
+    /// loads the schemas returned by the `/Schemas` endpoint.
+    ///
+    /// The json can be a `ListResponse`, with the schemas in its `Resources`, an array of schemas
+    /// or a single schema.
+    pub fn list_from_json(json: &str) -> Result<Vec<Schema>, Error> {
+        let value: JsonValue = serde_json::from_str(json).map_err(Error::invalid_schema)?;
+        let schemas = match value {
+            JsonValue::Object(mut object) if object.contains_key("Resources") => {
+                object.remove("Resources").unwrap_or_default()
+            }
+            JsonValue::Object(_) => JsonValue::Array(vec![value]),
+            value => value,
+        };
+        serde_json::from_value(schemas).map_err(Error::invalid_schema)
+    }
+
+    /// the definitions of the attributes identified by the schema URI, `None` is the schema itself.
+    pub fn attributes_of(&self, uri: Option<&str>) -> Option<&[AttributeDefinition]> {
+        match uri {
+            None => Some(&self.attributes),
+            Some(uri) => std::iter::once(self)
+                .chain(&self.extensions)
+                .find(|schema| schema.id.eq_ignore_ascii_case(uri))
+                .map(|schema| schema.attributes.as_slice()),
+        }
+    }
+
+    /// finds the definition of the attribute, or of the sub-attribute, addressed by the path
+    pub fn attribute_definition(&self, attr_path: &AttrPath) -> Option<&AttributeDefinition> {
+        find_attribute_definition(self.attributes_of(attr_path.uri().as_deref())?, attr_path)
+    }
+}
+
+impl FromStr for Schema {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_json::from_str(s).map_err(Error::invalid_schema)
+    }
+}
+
+/// finds the attribute addressed by the path among the given definitions, ignoring the schema URI
+pub(crate) fn find_attribute_definition<'s>(
+    attributes: &'s [AttributeDefinition],
+    attr_path: &AttrPath,
+) -> Option<&'s AttributeDefinition> {
+    let attribute = find_by_name(attributes, attr_path.attr_name())?;
+    match attr_path.sub_attr() {
+        None => Some(attribute),
+        Some(sub_attr) => find_by_name(&attribute.sub_attributes, sub_attr),
+    }
+}
+
+/// finds the attribute with the given name, ignoring the case
+pub(crate) fn find_by_name<'s>(
+    attributes: &'s [AttributeDefinition],
+    name: &str,
+) -> Option<&'s AttributeDefinition> {
+    attributes
+        .iter()
+        .find(|attribute| attribute.name.eq_ignore_ascii_case(name))
+}
+
+/// The definition of an attribute, as described by RFC 7643 section 7.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttributeDefinition {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub attr_type: AttributeType,
+    #[serde(default)]
+    pub multi_valued: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub required: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub canonical_values: Vec<String>,
+    #[serde(default)]
+    pub case_exact: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sub_attributes: Vec<AttributeDefinition>,
+}
+
+impl AttributeDefinition {
+    /// a single-valued, optional and case-insensitive attribute
+    pub fn new(name: impl Into<String>, attr_type: AttributeType) -> Self {
+        Self {
+            name: name.into(),
+            attr_type,
+            multi_valued: false,
+            description: None,
+            required: false,
+            canonical_values: vec![],
+            case_exact: false,
+            sub_attributes: vec![],
+        }
+    }
+
+    pub fn multi_valued(mut self) -> Self {
+        self.multi_valued = true;
+        self
+    }
+
+    pub fn required(mut self) -> Self {
+        self.required = true;
+        self
+    }
+
+    pub fn case_exact(mut self) -> Self {
+        self.case_exact = true;
+        self
+    }
+
+    pub fn with_sub_attributes(mut self, sub_attributes: Vec<AttributeDefinition>) -> Self {
+        self.sub_attributes = sub_attributes;
+        self
+    }
+
+    pub fn with_canonical_values(mut self, canonical_values: &[&str]) -> Self {
+        self.canonical_values = canonical_values.iter().map(ToString::to_string).collect();
+        self
+    }
+}
+
+/// The data types of RFC 7643 section 2.3
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AttributeType {
+    String,
+    Boolean,
+    Decimal,
+    Integer,
+    DateTime,
+    Binary,
+    Reference,
+    Complex,
+}
+
+impl AttributeType {
+    /// the type as it's written in a schema
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AttributeType::String => "string",
+            AttributeType::Boolean => "boolean",
+            AttributeType::Decimal => "decimal",
+            AttributeType::Integer => "integer",
+            AttributeType::DateTime => "dateTime",
+            AttributeType::Binary => "binary",
+            AttributeType::Reference => "reference",
+            AttributeType::Complex => "complex",
+        }
+    }
+}
+
+impl Display for AttributeType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
@@ -0,0 +1,164 @@
+use std::fmt::{Display, Formatter};
+
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+
+use crate::error::{Error, PatchError};
+
+#[cfg(test)]
+#[path = "test/scim_error_test.rs"]
+mod scim_error_test;
+
+/// An error response of the SCIM protocol, as described by RFC 7644 section 3.12.
+///
+/// It serializes to the `urn:ietf:params:scim:api:messages:2.0:Error` json, so it can be returned
+/// as it is by an http handler:
+///
+/// ```
+/// use scim_filter::{CompiledFilter, ScimError};
+///
+/// let error = CompiledFilter::new("userName eq").unwrap_err();
+/// let body = serde_json::to_value(ScimError::from(&error)).unwrap();
+/// assert_eq!("400", body["status"]);
+/// assert_eq!("invalidFilter", body["scimType"]);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScimError {
+    pub status: u16,
+    pub scim_type: Option<ScimType>,
+    pub detail: String,
+}
+
+impl ScimError {
+    pub const SCHEMA_URI: &'static str = "urn:ietf:params:scim:api:messages:2.0:Error";
+
+    pub fn new(status: u16, scim_type: Option<ScimType>, detail: impl Into<String>) -> Self {
+        Self {
+            status,
+            scim_type,
+            detail: detail.into(),
+        }
+    }
+
+    /// a 400 error, for a request that doesn't follow the protocol
+    pub fn bad_request(scim_type: ScimType, detail: impl Into<String>) -> Self {
+        Self::new(400, Some(scim_type), detail)
+    }
+
+    /// a 500 error, for a failure of the service provider
+    pub fn internal(detail: impl Into<String>) -> Self {
+        Self::new(500, None, detail)
+    }
+}
+
+impl Serialize for ScimError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("ScimError", 4)?;
+        state.serialize_field("schemas", &[Self::SCHEMA_URI])?;
+        // the status is a string in the error json
+        state.serialize_field("status", &self.status.to_string())?;
+        match &self.scim_type {
+            Some(scim_type) => state.serialize_field("scimType", scim_type)?,
+            None => state.skip_field("scimType")?,
+        }
+        state.serialize_field("detail", &self.detail)?;
+        state.end()
+    }
+}
+
+impl Display for ScimError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.status)?;
+        if let Some(scim_type) = &self.scim_type {
+            write!(f, " {}", scim_type.as_str())?;
+        }
+        write!(f, ": {}", self.detail)
+    }
+}
+
+impl std::error::Error for ScimError {}
+
+/// The `scimType` of the 400 errors, RFC 7644 table 9
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ScimType {
+    InvalidFilter,
+    TooMany,
+    Uniqueness,
+    Mutability,
+    InvalidSyntax,
+    InvalidPath,
+    NoTarget,
+    InvalidValue,
+    InvalidVers,
+    Sensitive,
+}
+
+impl ScimType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScimType::InvalidFilter => "invalidFilter",
+            ScimType::TooMany => "tooMany",
+            ScimType::Uniqueness => "uniqueness",
+            ScimType::Mutability => "mutability",
+            ScimType::InvalidSyntax => "invalidSyntax",
+            ScimType::InvalidPath => "invalidPath",
+            ScimType::NoTarget => "noTarget",
+            ScimType::InvalidValue => "invalidValue",
+            ScimType::InvalidVers => "invalidVers",
+            ScimType::Sensitive => "sensitive",
+        }
+    }
+}
+
+impl From<&Error> for ScimError {
+    fn from(error: &Error) -> Self {
+        match error {
+            // the filter can't be parsed, or it compares attributes in a way that's not supported
+            Error::Parser(_)
+            | Error::NumberOutOfRange(_)
+            | Error::Validation(_)
+            | Error::InvalidFilter
+            | Error::WrongOperator(_, _)
+            | Error::MalformedBoolean(_)
+            | Error::MalformedNumber(_)
+            | Error::MalformedString(_)
+            | Error::MalformedDatetime(_)
+            | Error::InvalidComparisonValue(_) => {
+                ScimError::bad_request(ScimType::InvalidFilter, error.to_string())
+            }
+            // the sort parameters of a query
+            Error::InvalidSortBy(_, _) | Error::InvalidSortOrder(_) => {
+                ScimError::bad_request(ScimType::InvalidValue, error.to_string())
+            }
+            Error::Patch(patch_error) => {
+                let scim_type = match patch_error {
+                    PatchError::InvalidOperation(_) => ScimType::InvalidSyntax,
+                    PatchError::InvalidPath(_, _) => ScimType::InvalidPath,
+                    PatchError::NoTarget(_) | PatchError::MissingPath => ScimType::NoTarget,
+                    PatchError::MissingValue(_) | PatchError::InvalidValue(_) => {
+                        ScimType::InvalidValue
+                    }
+                };
+                ScimError::bad_request(scim_type, error.to_string())
+            }
+            // the resources and the schemas are provided by the service provider
+            Error::SerializationError(_) | Error::InvalidResource | Error::InvalidSchema(_) => {
+                ScimError::internal(error.to_string())
+            }
+        }
+    }
+}
+
+impl From<Error> for ScimError {
+    fn from(error: Error) -> Self {
+        Self::from(&error)
+    }
+}
+
+impl Error {
+    /// the error response of the SCIM protocol for this error
+    pub fn to_scim_error(&self) -> ScimError {
+        ScimError::from(self)
+    }
+}
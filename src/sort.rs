@@ -0,0 +1,267 @@
+//! Sorting of resources, as described by RFC 7644 section 3.4.2.3.
+
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+use caseless::default_case_fold_str;
+use chrono::{DateTime, FixedOffset};
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+use crate::error::Error;
+use crate::parser::{attr_path_parser, AttrPath};
+use crate::schema::{AttributeDefinition, AttributeType};
+use crate::{AttrValue, Schema, ScimResource};
+
+#[cfg(test)]
+#[path = "test/sort_test.rs"]
+mod sort_test;
+
+/// The `sortOrder` of a query
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SortOrder {
+    #[default]
+    Ascending,
+    Descending,
+}
+
+impl FromStr for SortOrder {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("ascending") {
+            Ok(SortOrder::Ascending)
+        } else if s.eq_ignore_ascii_case("descending") {
+            Ok(SortOrder::Descending)
+        } else {
+            Err(Error::InvalidSortOrder(s.to_string()))
+        }
+    }
+}
+
+/// The `sortBy` and `sortOrder` parameters of a query, that sort the resources by an attribute.
+///
+/// The values are compared according to their type: strings are compared case-insensitively,
+/// unless the schema declares the attribute `caseExact`, while numbers, booleans and datetimes are
+/// compared by value. A multi-valued attribute is sorted by its primary value, or by its first
+/// value. Resources without a value are sorted last, whatever the order.
+///
+/// ```
+/// use scim_filter::{scim_filter, Sort};
+///
+/// let users = vec![
+///     serde_json::json!({"userName": "bjensen", "name": {"familyName": "Jensen"}}),
+///     serde_json::json!({"userName": "jsmith", "name": {"familyName": "Smith"}}),
+///     serde_json::json!({"userName": "jdoe"}),
+/// ];
+/// let sort = Sort::from_params("name.familyName", Some("descending")).unwrap();
+/// let sorted = sort.sort(scim_filter("userName pr", users).unwrap()).unwrap();
+/// assert_eq!(
+///     vec!["jsmith", "bjensen", "jdoe"],
+///     sorted.iter().map(|user| &user["userName"]).collect::<Vec<_>>()
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sort {
+    attr_path: AttrPath,
+    order: SortOrder,
+    schema: Option<Schema>,
+}
+
+impl Sort {
+    /// sorts in ascending order by the given attribute path
+    pub fn new(sort_by: &str) -> Result<Self, Error> {
+        let attr_path = attr_path_parser(sort_by)
+            .map_err(|error| Error::InvalidSortBy(sort_by.to_string(), error.to_string()))?;
+        Ok(Self {
+            attr_path,
+            order: SortOrder::default(),
+            schema: None,
+        })
+    }
+
+    /// reads the `sortBy` and `sortOrder` query parameters, the order is ascending by default
+    pub fn from_params(sort_by: &str, sort_order: Option<&str>) -> Result<Self, Error> {
+        let order = sort_order.map(str::parse).transpose()?.unwrap_or_default();
+        Ok(Self::new(sort_by)?.with_order(order))
+    }
+
+    pub fn with_order(mut self, order: SortOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// compares the values like the matcher, see [`crate::MatchOptions::with_schema`]
+    pub fn with_schema(mut self, schema: Schema) -> Self {
+        self.schema = Some(schema);
+        self
+    }
+
+    pub fn attr_path(&self) -> &AttrPath {
+        &self.attr_path
+    }
+
+    pub fn order(&self) -> SortOrder {
+        self.order
+    }
+
+    pub fn schema(&self) -> Option<&Schema> {
+        self.schema.as_ref()
+    }
+
+    /// sorts the resources in place, reading their attributes through [`ScimResource`]. Resources
+    /// with equal values keep their order.
+    pub fn sort_resources<R: ScimResource>(&self, resources: &mut [R]) {
+        resources.sort_by_cached_key(|resource| self.sort_key(resource));
+    }
+
+    /// returns the resources sorted, the resources are serialized to json first, like by
+    /// [`crate::CompiledFilter::filter`]
+    pub fn sort<T: Serialize>(
+        &self,
+        resources: impl IntoIterator<Item = T>,
+    ) -> Result<Vec<T>, Error> {
+        let mut keyed = resources
+            .into_iter()
+            .map(|resource| Ok((self.sort_key(&serde_json::to_value(&resource)?), resource)))
+            .collect::<Result<Vec<_>, Error>>()?;
+        keyed.sort_by(|(left, _), (right, _)| left.cmp(right));
+        Ok(keyed.into_iter().map(|(_, resource)| resource).collect())
+    }
+
+    /// the position of the resource among the sorted ones
+    pub(crate) fn sort_key<R: ScimResource + ?Sized>(&self, resource: &R) -> SortKey {
+        SortKey {
+            value: SortValue::new(&self.sort_value(resource), self.definition()),
+            order: self.order,
+        }
+    }
+
+    /// the value the resource is sorted by
+    fn sort_value<'r, R: ScimResource + ?Sized>(&self, resource: &'r R) -> AttrValue<'r> {
+        match (
+            self.attr_path.extract_base_value(resource),
+            self.attr_path.sub_attr(),
+        ) {
+            (AttrValue::Multi(values), sub_attr) => match primary_or_first(values) {
+                complex @ AttrValue::Complex(_) => {
+                    complex.into_sub_attr(sub_attr.as_deref().unwrap_or("value"))
+                }
+                value if sub_attr.is_none() => value,
+                _ => AttrValue::Null,
+            },
+            (value, Some(sub_attr)) => value.into_sub_attr(sub_attr),
+            (value, None) => value,
+        }
+    }
+
+    /// the definition of the sorted values, the `value` sub-attribute for a complex attribute
+    fn definition(&self) -> Option<&AttributeDefinition> {
+        let definition = self
+            .schema
+            .as_ref()?
+            .attribute_definition(&self.attr_path)?;
+        match definition.attr_type {
+            AttributeType::Complex => definition
+                .sub_attributes
+                .iter()
+                .find(|sub_attribute| sub_attribute.name.eq_ignore_ascii_case("value")),
+            _ => Some(definition),
+        }
+    }
+}
+
+/// the value marked as primary among the values of a multi-valued attribute, or the first one
+fn primary_or_first(values: Vec<AttrValue<'_>>) -> AttrValue<'_> {
+    let primary = values.iter().position(|value| {
+        matches!(value, AttrValue::Complex(complex) if matches!(complex.get_attr("primary"), AttrValue::Bool(true)))
+    });
+    values
+        .into_iter()
+        .nth(primary.unwrap_or(0))
+        .unwrap_or(AttrValue::Null)
+}
+
+/// A value that can be sorted, values of different types are sorted by type
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum SortValue {
+    Bool(bool),
+    Number(Decimal),
+    DateTime(DateTime<FixedOffset>),
+    String(String),
+}
+
+impl SortValue {
+    /// converts the value to the type declared by the definition, if any. Null and complex
+    /// values have no sort value.
+    fn new(value: &AttrValue, definition: Option<&AttributeDefinition>) -> Option<Self> {
+        match value {
+            AttrValue::Null | AttrValue::Complex(_) | AttrValue::Multi(_) => None,
+            AttrValue::Bool(value) => Some(SortValue::Bool(*value)),
+            AttrValue::Number(value) => Some(SortValue::Number(*value)),
+            AttrValue::DateTime(value) => Some(SortValue::DateTime(*value)),
+            AttrValue::String(value) => Some(Self::from_string(value, definition)),
+        }
+    }
+
+    fn from_string(value: &str, definition: Option<&AttributeDefinition>) -> Self {
+        let attr_type = definition.map(|definition| definition.attr_type);
+        let case_exact = definition.is_some_and(|definition| definition.case_exact);
+        match attr_type {
+            Some(AttributeType::DateTime) => {
+                if let Ok(datetime) = DateTime::parse_from_rfc3339(value) {
+                    return SortValue::DateTime(datetime);
+                }
+            }
+            Some(AttributeType::Integer | AttributeType::Decimal) => {
+                if let Ok(number) = Decimal::from_str(value) {
+                    return SortValue::Number(number);
+                }
+            }
+            // without a schema a string in the datetime or number format is compared like the
+            // matcher does, as a datetime or a number
+            None => {
+                if let Ok(datetime) = DateTime::parse_from_rfc3339(value) {
+                    return SortValue::DateTime(datetime);
+                }
+                if let Ok(number) = Decimal::from_str_exact(value) {
+                    return SortValue::Number(number);
+                }
+            }
+            _ => {}
+        }
+        if case_exact || attr_type == Some(AttributeType::Binary) {
+            SortValue::String(value.to_string())
+        } else {
+            SortValue::String(default_case_fold_str(value))
+        }
+    }
+}
+
+/// The position of a resource in the sorted list
+#[derive(PartialEq, Eq)]
+pub(crate) struct SortKey {
+    value: Option<SortValue>,
+    order: SortOrder,
+}
+
+impl Ord for SortKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (&self.value, &other.value) {
+            (Some(value), Some(other_value)) => match self.order {
+                SortOrder::Ascending => value.cmp(other_value),
+                SortOrder::Descending => other_value.cmp(value),
+            },
+            // resources without a value are last in both orders
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        }
+    }
+}
+
+impl PartialOrd for SortKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
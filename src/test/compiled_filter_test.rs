@@ -0,0 +1,124 @@
+use std::str::FromStr;
+
+use serde::Serialize;
+use serde_json::json;
+use test_case::test_case;
+
+use crate::parser::CompareOp;
+use crate::{
+    AttributeDefinition, AttributeType, CompiledFilter, Error, MatchOptions, MismatchPolicy, Schema,
+};
+
+#[derive(Debug, Serialize, PartialEq)]
+struct User {
+    #[serde(rename = "userName")]
+    user_name: String,
+    active: bool,
+}
+
+fn users() -> Vec<User> {
+    vec![
+        User {
+            user_name: "jlennon".to_string(),
+            active: true,
+        },
+        User {
+            user_name: "pmccartney".to_string(),
+            active: false,
+        },
+    ]
+}
+
+#[test]
+fn compiled_filter_from_str() {
+    let filter = CompiledFilter::from_str("userName eq \"jlennon\"").unwrap();
+    assert!(filter.matches(&users()[0]).unwrap());
+    assert!(!filter.matches(&users()[1]).unwrap());
+}
+
+#[test]
+fn compiled_filter_try_from() {
+    let filter = CompiledFilter::try_from("active eq false").unwrap();
+    assert_eq!(vec![&users()[1]], filter.filter(users().iter()).unwrap());
+}
+
+#[test_case("userName sw \"j\"", true; "matching value")]
+#[test_case("userName sw \"p\"", false; "not matching value")]
+fn compiled_filter_matches_value(input: &str, expected: bool) {
+    let filter: CompiledFilter = input.parse().unwrap();
+    let user = json!({"userName": "jlennon", "active": true});
+    assert_eq!(expected, filter.matches_value(&user).unwrap());
+}
+
+#[test]
+fn compiled_filter_is_reusable() {
+    let filter: CompiledFilter = "active eq true".parse().unwrap();
+    for _ in 0..3 {
+        assert_eq!(1, filter.filter(users()).unwrap().len());
+    }
+}
+
+#[test]
+fn compiled_filter_outlives_its_input() {
+    let input = String::from("userName  EQ \"jlennon\"");
+    let filter = CompiledFilter::new(&input).unwrap();
+    drop(input);
+    assert_eq!("userName eq \"jlennon\"", filter.to_string());
+    assert_eq!(1, filter.filter(users()).unwrap().len());
+}
+
+#[test]
+fn compiled_filter_with_wrong_syntax() {
+    let filter = CompiledFilter::from_str("userName eq");
+    assert!(matches!(filter, Err(Error::Parser(_))));
+}
+
+#[test]
+fn compiled_filter_stops_at_first_error() {
+    let filter: CompiledFilter = "active gt true".parse().unwrap();
+    assert!(filter.filter(users()).is_err());
+}
+
+#[test]
+fn compiled_filter_filter_values_returns_references() {
+    let filter: CompiledFilter = "userName ew \"y\"".parse().unwrap();
+    let values = vec![
+        json!({"userName": "jlennon"}),
+        json!({"userName": "pmccartney"}),
+        json!({"userName": "rstarr"}),
+    ];
+    let matching = filter.filter_values(&values).unwrap();
+    assert_eq!(1, matching.len());
+    assert!(std::ptr::eq(&values[1], matching[0]));
+}
+
+#[test_case(true; "schema before options")]
+#[test_case(false; "options before schema")]
+fn schema_and_options_are_both_kept(schema_first: bool) {
+    let schema = Schema::new(
+        Schema::USER_URI,
+        vec![AttributeDefinition::new(
+            "employeeNumber",
+            AttributeType::String,
+        )],
+    );
+    let filter = CompiledFilter::new("employeeNumber eq \"42\"").unwrap();
+    let filter = if schema_first {
+        filter
+            .with_schema(schema.clone())
+            .with_options(MatchOptions::strict())
+    } else {
+        filter
+            .with_options(MatchOptions::strict())
+            .with_schema(schema.clone())
+    };
+    assert_eq!(Some(&schema), filter.schema());
+    assert_eq!(
+        MismatchPolicy::Error,
+        filter.options().policy(CompareOp::Equal)
+    );
+    // without the schema the strict options reject a string that looks like a number
+    assert!(filter
+        .matches_value(&json!({"employeeNumber": "42"}))
+        .unwrap());
+}
@@ -0,0 +1,96 @@
+use serde_json::json;
+use test_case::test_case;
+
+use crate::parser::{scim_filter_parser, AttrExpData, Filter};
+use crate::{AttributeType, CompiledFilter, Schema};
+
+fn enterprise_user_schema() -> Schema {
+    Schema::user().with_extension(Schema::enterprise_user())
+}
+
+#[test_case(Schema::user(), "userName", AttributeType::String, false; "user name")]
+#[test_case(Schema::user(), "id", AttributeType::String, true; "id")]
+#[test_case(Schema::user(), "externalId", AttributeType::String, true; "external id")]
+#[test_case(Schema::user(), "emails.value", AttributeType::String, false; "email value")]
+#[test_case(Schema::user(), "emails.primary", AttributeType::Boolean, false; "email primary")]
+#[test_case(Schema::user(), "meta.lastModified", AttributeType::DateTime, false; "last modified")]
+#[test_case(Schema::user(), "meta.resourceType", AttributeType::String, true; "resource type")]
+#[test_case(Schema::user(), "meta.location", AttributeType::Reference, true; "location")]
+#[test_case(Schema::user(), "meta.version", AttributeType::String, true; "version")]
+#[test_case(Schema::user(), "x509Certificates.value", AttributeType::Binary, true; "certificate")]
+#[test_case(Schema::user(), "active", AttributeType::Boolean, false; "active")]
+#[test_case(enterprise_user_schema(), "urn:ietf:params:scim:schemas:extension:enterprise:2.0:User:manager.value", AttributeType::String, false; "manager value")]
+#[test_case(Schema::group(), "members.value", AttributeType::String, false; "member value")]
+#[test_case(Schema::group(), "meta.created", AttributeType::DateTime, false; "group created")]
+#[test_case(Schema::service_provider_config(), "filter.maxResults", AttributeType::Integer, false; "max results")]
+#[test_case(Schema::service_provider_config(), "authenticationSchemes.specUri", AttributeType::Reference, false; "authentication scheme")]
+fn core_schema_attribute(
+    schema: Schema,
+    attribute: &str,
+    attr_type: AttributeType,
+    case_exact: bool,
+) {
+    let Ok(Filter::AttrExp(AttrExpData::Present(attr_path))) =
+        scim_filter_parser(&format!("{} pr", attribute))
+    else {
+        panic!("{} should be a valid attribute path", attribute);
+    };
+    let definition = schema.attribute_definition(&attr_path).unwrap();
+    assert_eq!(attr_type, definition.attr_type);
+    assert_eq!(case_exact, definition.case_exact);
+}
+
+#[test]
+fn extension_attribute_needs_the_schema_uri() {
+    let schema = enterprise_user_schema();
+    let attr_path = |attribute: &str| {
+        let Ok(Filter::AttrExp(AttrExpData::Present(attr_path))) =
+            scim_filter_parser(&format!("{} pr", attribute))
+        else {
+            panic!("{} should be a valid attribute path", attribute);
+        };
+        attr_path
+    };
+    assert!(schema
+        .attribute_definition(&attr_path("manager.value"))
+        .is_none());
+    assert!(schema
+        .attribute_definition(&attr_path(
+            "urn:ietf:params:scim:schemas:extension:enterprise:2.0:User:manager.value"
+        ))
+        .is_some());
+}
+
+#[test_case(Schema::user(); "user")]
+#[test_case(Schema::group(); "group")]
+#[test_case(Schema::enterprise_user(); "enterprise user")]
+#[test_case(Schema::service_provider_config(); "service provider config")]
+fn core_schema_json_roundtrip(schema: Schema) {
+    let json = serde_json::to_string(&schema).unwrap();
+    assert_eq!(schema, json.parse().unwrap());
+}
+
+#[test_case("userName eq \"BJENSEN@EXAMPLE.COM\"", true; "case insensitive user name")]
+#[test_case("id eq \"2819C223-7F76-453A-919D-413861904646\"", false; "case exact id")]
+#[test_case("externalId eq \"701984\"", true; "numeric external id")]
+#[test_case("meta.lastModified gt \"2011-01-01T00:00:00Z\"", true; "last modified")]
+#[test_case("emails[type eq \"work\" and value co \"@EXAMPLE.com\"]", true; "emails value path")]
+#[test_case("urn:ietf:params:scim:schemas:extension:enterprise:2.0:User:manager.value eq \"26118915-6090-4610-87e4-49d8ca9f808d\"", true; "manager value")]
+#[test_case("manager.value eq \"26118915-6090-4610-87e4-49d8ca9f808d\"", false; "manager value without schema uri")]
+fn match_with_core_schema(filter: &str, expected: bool) {
+    let user = json!({
+        "schemas": [Schema::USER_URI, Schema::ENTERPRISE_USER_URI],
+        "id": "2819c223-7f76-453a-919d-413861904646",
+        "externalId": "701984",
+        "userName": "bjensen@example.com",
+        "emails": [{"value": "bjensen@example.com", "type": "work", "primary": true}],
+        "meta": {"lastModified": "2011-05-13T04:42:34Z"},
+        "urn:ietf:params:scim:schemas:extension:enterprise:2.0:User": {
+            "manager": {"value": "26118915-6090-4610-87e4-49d8ca9f808d"}
+        }
+    });
+    let filter = CompiledFilter::new(filter)
+        .unwrap()
+        .with_schema(enterprise_user_schema());
+    assert_eq!(expected, filter.matches_value(&user).unwrap());
+}
@@ -0,0 +1,92 @@
+use std::cell::Cell;
+
+use serde_json::{json, Value};
+
+use crate::{CompiledFilter, FilterExt};
+
+fn resources() -> Vec<Value> {
+    vec![
+        json!({"name": "a", "number": 1}),
+        json!({"name": "b", "number": true}),
+        json!({"name": "c", "number": 3}),
+        json!({"name": "d", "number": 4}),
+    ]
+}
+
+fn names(resources: Vec<Value>) -> Vec<String> {
+    resources
+        .into_iter()
+        .map(|resource| resource["name"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn filter_iter_yields_matching_resources() {
+    let filter: CompiledFilter = "number ne 3".parse().unwrap();
+    let matching: Result<Vec<_>, _> = resources().into_iter().scim_filter(&filter).collect();
+    assert_eq!(vec!["a", "d"], names(matching.unwrap()));
+}
+
+#[test]
+fn filter_iter_can_skip_errors() {
+    let filter: CompiledFilter = "number gt 2".parse().unwrap();
+    let matching = resources()
+        .into_iter()
+        .scim_filter(&filter)
+        .filter_map(Result::ok)
+        .collect();
+    assert_eq!(vec!["c", "d"], names(matching));
+}
+
+#[test]
+fn filter_iter_can_stop_at_first_error() {
+    let filter: CompiledFilter = "number gt 2".parse().unwrap();
+    let mut iter = resources().into_iter().scim_filter(&filter);
+    assert!(iter.next().unwrap().is_err());
+    let matching: Vec<_> = iter.map(Result::unwrap).collect();
+    assert_eq!(vec!["c", "d"], names(matching));
+}
+
+#[test]
+fn filter_iter_is_lazy() {
+    let filter: CompiledFilter = "number gt 0".parse().unwrap();
+    let consumed = Cell::new(0);
+    let mut iter = resources()
+        .into_iter()
+        .inspect(|_| consumed.set(consumed.get() + 1))
+        .scim_filter(&filter);
+
+    assert_eq!(0, consumed.get());
+    assert!(iter.next().unwrap().is_ok());
+    assert_eq!(1, consumed.get());
+}
+
+#[test]
+fn compiled_filter_filter_iter_accepts_into_iterator() {
+    let filter: CompiledFilter = "name eq \"d\"".parse().unwrap();
+    let resources = resources();
+    let matching: Vec<_> = filter.filter_iter(&resources).map(Result::unwrap).collect();
+    assert_eq!(vec![&resources[3]], matching);
+}
+
+#[test]
+fn filter_iter_reads_resources_without_copying_them() {
+    let filter: CompiledFilter = "number gt 2".parse().unwrap();
+    let resources = resources();
+    let mut iter = resources.iter().scim_filter_resources(&filter);
+    assert!(iter.next().unwrap().is_err());
+    let matching: Vec<_> = iter.map(Result::unwrap).collect();
+    assert!(std::ptr::eq(&resources[2], matching[0]));
+    assert!(std::ptr::eq(&resources[3], matching[1]));
+}
+
+#[test]
+fn compiled_filter_filter_resources_iter_accepts_into_iterator() {
+    let filter: CompiledFilter = "name eq \"d\"".parse().unwrap();
+    let resources = resources();
+    let matching: Vec<_> = filter
+        .filter_resources_iter(&resources)
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(vec![&resources[3]], matching);
+}
@@ -0,0 +1,33 @@
+use serde_json::json;
+
+use crate::ListResponse;
+
+#[test]
+fn list_response_json() {
+    let response = ListResponse::new(vec![json!({"userName": "bjensen"})], 3, 2);
+    assert_eq!(
+        json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:ListResponse"],
+            "totalResults": 3,
+            "itemsPerPage": 1,
+            "startIndex": 2,
+            "Resources": [{"userName": "bjensen"}]
+        }),
+        serde_json::to_value(response).unwrap()
+    );
+}
+
+#[test]
+fn empty_list_response_json() {
+    let response = ListResponse::<serde_json::Value>::new(vec![], 0, 1);
+    assert_eq!(
+        json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:ListResponse"],
+            "totalResults": 0,
+            "itemsPerPage": 0,
+            "startIndex": 1,
+            "Resources": []
+        }),
+        serde_json::to_value(response).unwrap()
+    );
+}
@@ -0,0 +1,182 @@
+use serde_json::json;
+use test_case::test_case;
+
+use crate::parser::{scim_filter_parser, CompareOp};
+use crate::{CompiledFilter, Error, MatchOptions, MismatchPolicy, MultiValuedNotEqual, Schema};
+
+fn resource() -> serde_json::Value {
+    json!({"userName": "bjensen", "active": true, "loginCount": 7})
+}
+
+fn r#match(filter: &str, options: &MatchOptions) -> Result<bool, Error> {
+    scim_filter_parser(filter)
+        .unwrap()
+        .r#match(&resource(), options)
+}
+
+#[test_case(CompareOp::Equal, MismatchPolicy::NoMatch; "equal")]
+#[test_case(CompareOp::NotEqual, MismatchPolicy::NoMatch; "not equal")]
+#[test_case(CompareOp::Contains, MismatchPolicy::NoMatch; "contains")]
+#[test_case(CompareOp::StartsWith, MismatchPolicy::NoMatch; "starts with")]
+#[test_case(CompareOp::EndsWith, MismatchPolicy::NoMatch; "ends with")]
+#[test_case(CompareOp::GreaterThan, MismatchPolicy::Error; "greater than")]
+#[test_case(CompareOp::GreaterThanOrEqual, MismatchPolicy::Error; "greater than or equal")]
+#[test_case(CompareOp::LessThan, MismatchPolicy::Error; "less than")]
+#[test_case(CompareOp::LessThanOrEqual, MismatchPolicy::Error; "less than or equal")]
+fn default_policies(compare_op: CompareOp, expected: MismatchPolicy) {
+    assert_eq!(expected, MatchOptions::default().policy(compare_op));
+}
+
+#[test_case("active eq 1"; "equal")]
+#[test_case("active ne 1"; "not equal")]
+#[test_case("active co \"t\""; "contains")]
+#[test_case("active gt 1"; "greater than")]
+#[test_case("loginCount sw \"7\""; "starts with on a number")]
+fn strict_mismatch_is_an_error(filter: &str) {
+    assert!(r#match(filter, &MatchOptions::strict()).is_err());
+}
+
+#[test_case("active eq 1"; "equal")]
+#[test_case("active ne 1"; "not equal")]
+#[test_case("active gt 1"; "greater than")]
+#[test_case("userName le 5"; "less than or equal")]
+fn lenient_mismatch_is_a_non_match(filter: &str) {
+    assert!(!r#match(filter, &MatchOptions::lenient()).unwrap());
+}
+
+#[test]
+fn default_options_keep_the_historical_behaviour() {
+    assert!(!r#match("active eq 1", &MatchOptions::default()).unwrap());
+    assert!(r#match("active gt 1", &MatchOptions::default()).is_err());
+}
+
+#[test]
+fn per_operator_policy() {
+    let options = MatchOptions::lenient().with_policy(CompareOp::Equal, MismatchPolicy::Error);
+    assert!(r#match("active eq 1", &options).is_err());
+    assert!(!r#match("active ne 1", &options).unwrap());
+    assert!(!r#match("active gt 1", &options).unwrap());
+}
+
+#[test]
+fn mismatch_in_a_logical_expression() {
+    let filter = "active eq 1 or userName eq \"bjensen\"";
+    assert!(r#match(filter, &MatchOptions::lenient()).unwrap());
+    assert!(r#match(filter, &MatchOptions::strict()).is_err());
+}
+
+#[test]
+fn comparisons_that_fit_are_not_affected() {
+    let filter = "userName eq \"BJENSEN\" and active eq true and loginCount gt 5";
+    assert!(r#match(filter, &MatchOptions::strict()).unwrap());
+}
+
+#[test]
+fn options_with_schema() {
+    let options = MatchOptions::strict().with_schema(Schema::user());
+    let filter = CompiledFilter::new("userName eq \"42\"")
+        .unwrap()
+        .with_options(options.clone());
+    assert_eq!(Some(&Schema::user()), filter.schema());
+    assert_eq!(&options, filter.options());
+    assert!(filter.matches_value(&json!({"userName": "42"})).unwrap());
+    assert!(filter.matches_value(&json!({"userName": 42})).is_err());
+}
+
+#[test_case("emails.type ne \"work\"", true; "an element differs")]
+#[test_case("emails.type ne \"other\"", true; "every element differs")]
+#[test_case("roles ne \"admin\"", false; "no values")]
+fn multi_valued_not_equal_is_any_element_differs_by_default(filter: &str, expected: bool) {
+    assert_eq!(
+        MultiValuedNotEqual::AnyElementDiffers,
+        MatchOptions::default().multi_valued_not_equal()
+    );
+    let user = multi_valued_user();
+    assert_eq!(
+        expected,
+        scim_filter_parser(filter)
+            .unwrap()
+            .r#match(&user, &MatchOptions::default())
+            .unwrap()
+    );
+}
+
+#[test_case("emails.type ne \"work\"", false; "an element is equal")]
+#[test_case("emails.type ne \"WORK\"", false; "an element is equal ignoring case")]
+#[test_case("emails.type ne \"other\"", true; "every element differs")]
+#[test_case("roles ne \"admin\"", true; "no values")]
+#[test_case("phoneNumbers.value ne \"555\"", false; "missing attribute without schema")]
+#[test_case("nickName ne \"babs\"", false; "null single-valued attribute is not affected")]
+#[test_case("emails.type eq \"work\"", true; "equal is not affected")]
+#[test_case("userName ne \"bjensen\"", false; "single-valued attribute is not affected")]
+fn multi_valued_not_equal_with_no_element_equal(filter: &str, expected: bool) {
+    let options =
+        MatchOptions::default().with_multi_valued_not_equal(MultiValuedNotEqual::NoElementEqual);
+    assert_eq!(
+        expected,
+        scim_filter_parser(filter)
+            .unwrap()
+            .r#match(&multi_valued_user(), &options)
+            .unwrap()
+    );
+}
+
+#[test_case("emails.type ne \"work\"", false; "an element is equal")]
+#[test_case("phoneNumbers.value ne \"555\"", true; "missing multi-valued attribute")]
+#[test_case("phoneNumbers ne null", false; "missing attribute is still equal to null")]
+#[test_case("nickName ne \"babs\"", false; "null single-valued attribute is not affected")]
+#[test_case("title ne \"Tour Guide\"", false; "missing single-valued attribute is not affected")]
+fn multi_valued_not_equal_with_schema(filter: &str, expected: bool) {
+    let options = MatchOptions::default()
+        .with_multi_valued_not_equal(MultiValuedNotEqual::NoElementEqual)
+        .with_schema(Schema::user());
+    let filter = CompiledFilter::new(filter).unwrap().with_options(options);
+    assert_eq!(
+        expected,
+        filter.matches_value(&multi_valued_user()).unwrap()
+    );
+}
+
+#[test_case("emails.value eq \"a@x.com\"", false, true; "an element matches")]
+#[test_case("emails.value eq \"b@x.com\"", false, false; "no element matches")]
+#[test_case("emails.value eq \"a@x.com\"", true, true; "an element matches with schema")]
+#[test_case("emails.value ne \"b@x.com\"", false, true; "an element differs")]
+fn multi_valued_with_mismatched_element(filter: &str, with_schema: bool, expected: bool) {
+    let user = json!({
+        "emails": [{"value": 42}, {"value": "a@x.com"}]
+    });
+    let mut filter = CompiledFilter::new(filter)
+        .unwrap()
+        .with_options(MatchOptions::lenient());
+    if with_schema {
+        filter = filter.with_schema(Schema::user());
+    }
+    assert_eq!(expected, filter.matches_value(&user).unwrap());
+}
+
+#[test_case(false; "without schema")]
+#[test_case(true; "with schema")]
+fn multi_valued_with_mismatched_element_is_an_error_when_strict(with_schema: bool) {
+    let user = json!({
+        "emails": [{"value": 42}, {"value": "a@x.com"}]
+    });
+    let mut filter = CompiledFilter::new("emails.value eq \"a@x.com\"")
+        .unwrap()
+        .with_options(MatchOptions::strict());
+    if with_schema {
+        filter = filter.with_schema(Schema::user());
+    }
+    assert!(filter.matches_value(&user).is_err());
+}
+
+fn multi_valued_user() -> serde_json::Value {
+    json!({
+        "userName": "bjensen",
+        "emails": [
+            {"value": "bjensen@example.com", "type": "work"},
+            {"value": "babs@jensen.org", "type": "home"}
+        ],
+        "nickName": null,
+        "roles": []
+    })
+}
@@ -1,8 +1,11 @@
+use std::borrow::Cow;
+
 use chrono::{DateTime, TimeZone, Utc};
 use serde::Serialize;
 use test_case::test_case;
 
-use crate::scim_filter;
+use crate::parser::{scim_filter_parser, AttrExpData, Filter, ValuePathData};
+use crate::{scim_filter, AttrValue};
 
 #[derive(Debug, Serialize, PartialEq)]
 struct Resource {
@@ -73,6 +76,7 @@ fn example_resources2() -> Vec<Resource> {
 #[test_case("bool eq true"; "one resource do match with equals boolean")]
 #[test_case("bool ne false"; "one resource do match with not equals boolean")]
 #[test_case("a eq \"test1\""; "one resource do match with equals")]
+#[test_case("a eq \"test\\u0031\""; "one resource do match with an escaped string")]
 #[test_case("b co \"est\""; "one resource do match with correct contains")]
 #[test_case("b sw \"te\""; "one resource do match with correct starts with")]
 #[test_case("c ew \"st3\""; "one resource do match with correct ends with")]
@@ -82,6 +86,7 @@ fn example_resources2() -> Vec<Resource> {
 #[test_case("a eq \"test1\" or b eq \"test3\""; "two resources with a logical or where one is wrong")]
 #[test_case("A eq \"test1\""; "matches should be case insensitive")]
 #[test_case("(a eq \"test1\" or b eq \"test3\") and c pr"; "complex filter 1")]
+#[test_case("a eq \"no-match\" and b eq \"test2\" or c eq \"test3\""; "and has precedence over or")]
 #[test_case("datetime gt \"2020-01-01T10:10:10Z\""; "filter with date that should match")]
 #[test_case("decimal gt 9.1"; "filter with decimal")]
 #[test_case("a eq \"test1\" and sub_resource[first co \"test-\" and second co \"test-\"]"; "filter with complex attribute match")]
@@ -103,6 +108,8 @@ fn example_resources2() -> Vec<Resource> {
 #[test_case("nested_multi_value.first eq \"test-first1\""; "nested multi-valued attribute with eq")]
 #[test_case("nested_multi_value.first ne \"test-firstZ\""; "nested multi-valued attribute with ne")]
 #[test_case("number eq 42"; "number match on single value")]
+#[test_case("number eq 4.2e1"; "number match with exponent")]
+#[test_case("number gt -5"; "number match with negative number")]
 #[test_case("multi_bool_value eq true"; "bool multi-valued attribute with Equal")]
 #[test_case("multi_bool_value ne false"; "bool multi-valued attribute with NotEqual")]
 #[test_case("bool eq true"; "bool(true) single-valued attribute with Equal")]
@@ -133,6 +140,7 @@ fn match_ok_with_one_resource(filter: &str) {
 #[test_case("c ew \"stX\""; "one resource do not match with wrong ends with")]
 #[test_case("d pr"; "one resource do not match with present")]
 #[test_case("a eq \"test1\" and b eq \"test2\" and (c eq \"wrong1\" or c eq \"wrong2\")"; "complex filter 2")]
+#[test_case("c eq \"test3\" and (a eq \"no-match\" or b eq \"test2\") and a eq \"no-match\" or b eq \"no-match\""; "and has precedence over or - no match")]
 #[test_case("datetime gt \"2022-01-01T10:10:10Z\""; "filter with date")]
 #[test_case("a eq \"test1\" and sub_resource[first co \"test-\" and second ew \"test-\"]"; "filter with complex attribute should not match")]
 #[test_case("multi_simple_value eq \"ZZZ\""; "simple multi-valued attribute")]
@@ -216,3 +224,157 @@ fn match_partial_with_two_resources(filter: &str, indexes: Vec<usize>) {
     }
     assert_eq!(expected, res.unwrap().iter().collect::<Vec<&Resource>>());
 }
+
+#[test_case("a"; "simple attribute")]
+#[test_case("sub_resource.first"; "sub attribute")]
+#[test_case("multi_simple_value"; "multi-valued attribute")]
+#[test_case("nested_multi_value.first"; "sub attribute of a multi-valued attribute")]
+fn extract_value_borrows_from_resource(attribute: &str) {
+    fn assert_borrowed(value: &AttrValue) {
+        match value {
+            AttrValue::String(string) => assert!(matches!(string, Cow::Borrowed(_))),
+            AttrValue::Multi(values) => values.iter().for_each(assert_borrowed),
+            value => panic!("{:?} should be a string", value),
+        }
+    }
+
+    let resource = serde_json::to_value(Resource::new("test1", "test2", "test3")).unwrap();
+    let Ok(Filter::AttrExp(AttrExpData::Present(attr_path))) =
+        scim_filter_parser(&format!("{} pr", attribute))
+    else {
+        panic!("{} should be a valid attribute path", attribute);
+    };
+    assert_borrowed(&attr_path.extract_value(&resource));
+}
+
+#[test_case("urn:ietf:params:scim:schemas:core:2.0:User:userName eq \"bjensen\"", true; "core schema attribute")]
+#[test_case("URN:IETF:PARAMS:SCIM:SCHEMAS:CORE:2.0:USER:USERNAME eq \"bjensen\"", true; "core schema is case insensitive")]
+#[test_case("urn:ietf:params:scim:schemas:core:2.0:User:name.familyName eq \"Jensen\"", true; "core schema sub attribute")]
+#[test_case("urn:ietf:params:scim:schemas:extension:enterprise:2.0:User:department eq \"Tour Operations\"", true; "extension schema attribute")]
+#[test_case("urn:ietf:params:scim:schemas:extension:enterprise:2.0:User:manager.displayName sw \"John\"", true; "extension schema sub attribute")]
+#[test_case("urn:ietf:params:scim:schemas:extension:enterprise:2.0:User:userName pr", false; "core attribute with extension schema")]
+#[test_case("urn:ietf:params:scim:schemas:extension:enterprise:2.0:User:employeeNumber pr and not (employeeNumber pr)", true; "extension attribute without schema")]
+#[test_case("urn:acme:scim:schemas:extension:2.0:User:employeeNumber pr", false; "missing extension")]
+fn match_with_schema_uri(filter: &str, expected: bool) {
+    let resource = serde_json::json!({
+        "schemas": [
+            "urn:ietf:params:scim:schemas:core:2.0:User",
+            "urn:ietf:params:scim:schemas:extension:enterprise:2.0:User"
+        ],
+        "userName": "bjensen",
+        "name": {"familyName": "Jensen"},
+        "urn:ietf:params:scim:schemas:extension:enterprise:2.0:User": {
+            "employeeNumber": "701984",
+            "department": "Tour Operations",
+            "manager": {"value": "26118915", "displayName": "John Smith"}
+        }
+    });
+    let res = scim_filter(filter, vec![&resource]);
+    assert_eq!(expected, !res.unwrap().is_empty());
+}
+
+#[test_case("userName eq \"BJENSEN\"", true; "equal")]
+#[test_case("userName ne \"BJENSEN\"", false; "not equal")]
+#[test_case("userName co \"JEN\"", true; "contains")]
+#[test_case("userName sw \"BJ\"", true; "starts with")]
+#[test_case("userName ew \"SEN\"", true; "ends with")]
+#[test_case("userName gt \"A\" and userName lt \"C\"", true; "ordering")]
+#[test_case("title eq \"RENÉE\"", true; "non ascii characters")]
+#[test_case("address eq \"STRASSE\"", true; "full case folding")]
+#[test_case("emails eq \"BABS@JENSEN.ORG\"", true; "multi-valued attribute")]
+fn strings_are_compared_case_insensitively(filter: &str, expected: bool) {
+    let resource = serde_json::json!({
+        "userName": "bjensen",
+        "title": "Renée",
+        "address": "Straße",
+        "emails": ["bjensen@example.com", "babs@jensen.org"]
+    });
+    let res = scim_filter(filter, vec![&resource]);
+    assert_eq!(expected, !res.unwrap().is_empty());
+}
+
+#[test_case("emails[type eq \"work\" and value ew \"example.com\"]", false; "conditions hold on different elements")]
+#[test_case("emails[type eq \"work\" and value ew \"jensen.org\"]", true; "conditions hold on the same element")]
+#[test_case("emails[type eq \"home\" and not (primary eq true)]", true; "not on the same element")]
+#[test_case("emails[type eq \"home\" or value ew \"example.com\"]", true; "or on different elements")]
+#[test_case("emails[type ne \"work\"]", true; "ne on one element")]
+#[test_case("emails[(type eq \"home\") and (primary pr)]", false; "presence on a different element")]
+fn value_path_is_evaluated_per_element(filter: &str, expected: bool) {
+    let resource = serde_json::json!({
+        "emails": [
+            {"value": "bjensen@jensen.org", "type": "work"},
+            {"value": "babs@example.com", "type": "home"},
+            {"value": "barbara@example.org", "type": "other", "primary": true}
+        ]
+    });
+    let res = scim_filter(filter, vec![&resource]);
+    assert_eq!(expected, !res.unwrap().is_empty());
+}
+
+#[test_case("Emails[type eq \"work\"]", "Emails.type eq \"work\""; "attribute name case")]
+#[test_case("emails[TYPE eq \"work\"]", "emails.TYPE eq \"work\""; "sub attribute name case")]
+#[test_case("urn:ietf:params:scim:schemas:core:2.0:User:emails[type eq \"work\"]", "urn:ietf:params:scim:schemas:core:2.0:User:emails.type eq \"work\""; "core schema uri")]
+#[test_case("urn:ietf:params:scim:schemas:extension:enterprise:2.0:User:manager[displayName sw \"John\"]", "urn:ietf:params:scim:schemas:extension:enterprise:2.0:User:manager.displayName sw \"John\""; "extension schema uri")]
+#[test_case("nickName[value pr]", "nickName.value pr"; "missing attribute")]
+fn value_path_resolves_attributes_like_attribute_expressions(
+    value_path_filter: &str,
+    attribute_filter: &str,
+) {
+    let resource = serde_json::json!({
+        "userName": "bjensen",
+        "name": {"familyName": "Jensen"},
+        "emails": [{"value": "bjensen@example.com", "type": "work"}],
+        "urn:ietf:params:scim:schemas:extension:enterprise:2.0:User": {
+            "manager": {"displayName": "John Smith"}
+        }
+    });
+    let value_path_result = !scim_filter(value_path_filter, vec![&resource])
+        .unwrap()
+        .is_empty();
+    let attribute_result = !scim_filter(attribute_filter, vec![&resource])
+        .unwrap()
+        .is_empty();
+    assert_eq!(attribute_result, value_path_result);
+}
+
+fn value_path(filter: &str) -> ValuePathData<'_> {
+    match scim_filter_parser(filter).unwrap() {
+        Filter::ValuePath(value_path_data) => value_path_data,
+        filter => panic!("{filter:?} is not a value path"),
+    }
+}
+
+#[test_case("emails[type eq \"work\"]", vec![0, 3], vec!["/Emails/0", "/Emails/3"]; "some elements")]
+#[test_case("emails[type eq \"fax\"]", vec![], vec![]; "no element")]
+#[test_case("EMAILS[value ew \".org\"]", vec![0, 2], vec!["/Emails/0", "/Emails/2"]; "case insensitive name")]
+#[test_case("name[familyName eq \"Jensen\"]", vec![0], vec!["/name"]; "single valued attribute")]
+#[test_case("nickName[value pr]", vec![], vec![]; "missing attribute")]
+#[test_case("urn:ietf:params:scim:schemas:core:2.0:User:Emails[primary eq true]", vec![2], vec!["/Emails/2"]; "core schema uri")]
+#[test_case("urn:example:custom/2.0:User:tags[value eq 1]", vec![1], vec!["/urn:example:custom~12.0:User/tags/1"]; "escaped pointer")]
+fn value_path_matching_elements(filter: &str, indices: Vec<usize>, pointers: Vec<&str>) {
+    let resource = serde_json::json!({
+        "name": {"familyName": "Jensen"},
+        "Emails": [
+            {"value": "bjensen@jensen.org", "type": "work"},
+            {"value": "babs@example.com", "type": "home"},
+            {"value": "barbara@example.org", "type": "other", "primary": true},
+            {"value": "bjensen@example.com", "type": "work"}
+        ],
+        "urn:example:custom/2.0:User": {
+            "tags": [{"value": 0}, {"value": 1}]
+        }
+    });
+    let value_path = value_path(filter);
+    assert_eq!(
+        indices,
+        value_path
+            .matching_elements(&resource, &Default::default())
+            .unwrap()
+    );
+    assert_eq!(
+        pointers,
+        value_path
+            .matching_pointers(&resource, &Default::default())
+            .unwrap()
+    );
+}
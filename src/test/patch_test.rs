@@ -0,0 +1,110 @@
+use serde_json::{json, Value as JsonValue};
+use test_case::test_case;
+
+use crate::error::{Error, PatchError};
+use crate::{PatchOp, PatchOperation};
+
+fn user() -> JsonValue {
+    json!({
+        "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+        "userName": "bjensen",
+        "name": {"givenName": "Barbara", "familyName": "Jensen"},
+        "emails": [
+            {"value": "bjensen@example.com", "type": "work", "primary": true},
+            {"value": "babs@jensen.org", "type": "home"}
+        ],
+        "active": true
+    })
+}
+
+fn patch(operation: JsonValue) -> Result<JsonValue, Error> {
+    let operation: PatchOp = serde_json::from_value(operation)?;
+    let mut user = user();
+    operation.apply(&mut user, &Default::default())?;
+    Ok(user)
+}
+
+#[test_case(json!({"op": "add", "value": {"nickName": "Babs"}}), "/nickName", json!("Babs"); "add without path")]
+#[test_case(json!({"op": "add", "value": {"name": {"middleName": "Jane"}}}), "/name", json!({"givenName": "Barbara", "familyName": "Jensen", "middleName": "Jane"}); "add without path to complex attribute")]
+#[test_case(json!({"op": "add", "path": "nickName", "value": "Babs"}), "/nickName", json!("Babs"); "add new attribute")]
+#[test_case(json!({"op": "add", "path": "userName", "value": "babs"}), "/userName", json!("babs"); "add to single valued attribute")]
+#[test_case(json!({"op": "add", "path": "emails", "value": [{"value": "babs@example.org", "type": "other"}]}), "/emails/2", json!({"value": "babs@example.org", "type": "other"}); "add to multi valued attribute")]
+#[test_case(json!({"op": "add", "path": "emails", "value": {"value": "babs@example.org"}}), "/emails/2", json!({"value": "babs@example.org"}); "add single value to multi valued attribute")]
+#[test_case(json!({"op": "add", "path": "emails", "value": [{"value": "babs@jensen.org", "type": "home"}]}), "/emails/2", JsonValue::Null; "add existing value")]
+#[test_case(json!({"op": "add", "path": "EMAILS", "value": [{"value": "babs@example.org"}]}), "/emails/2", json!({"value": "babs@example.org"}); "add with case insensitive name")]
+#[test_case(json!({"op": "add", "path": "name", "value": {"middleName": "Jane"}}), "/name", json!({"givenName": "Barbara", "familyName": "Jensen", "middleName": "Jane"}); "add to complex attribute")]
+#[test_case(json!({"op": "add", "path": "name.givenName", "value": "Babs"}), "/name/givenName", json!("Babs"); "add sub attribute")]
+#[test_case(json!({"op": "add", "path": "addresses.country", "value": "US"}), "/addresses", json!({"country": "US"}); "add sub attribute of new attribute")]
+#[test_case(json!({"op": "add", "path": "urn:ietf:params:scim:schemas:core:2.0:User:displayName", "value": "Babs"}), "/displayName", json!("Babs"); "add with core schema uri")]
+#[test_case(json!({"op": "add", "path": "urn:ietf:params:scim:schemas:extension:enterprise:2.0:User:department", "value": "Tour"}), "/urn:ietf:params:scim:schemas:extension:enterprise:2.0:User", json!({"department": "Tour"}); "add with extension schema uri")]
+#[test_case(json!({"op": "add", "path": "urn:ietf:params:scim:schemas:extension:enterprise:2.0:User:department", "value": "Tour"}), "/schemas/1", json!("urn:ietf:params:scim:schemas:extension:enterprise:2.0:User"); "add extension schema")]
+#[test_case(json!({"op": "add", "path": "emails[type eq \"work\"].display", "value": "Work"}), "/emails/0/display", json!("Work"); "add sub attribute of filtered values")]
+#[test_case(json!({"op": "add", "path": "emails[type eq \"home\"]", "value": {"primary": false}}), "/emails/1", json!({"value": "babs@jensen.org", "type": "home", "primary": false}); "add to filtered values")]
+#[test_case(json!({"op": "Add", "path": "nickName", "value": "Babs"}), "/nickName", json!("Babs"); "case insensitive operation")]
+#[test_case(json!({"op": "replace", "value": {"active": false}}), "/active", json!(false); "replace without path")]
+#[test_case(json!({"op": "replace", "path": "userName", "value": "babs"}), "/userName", json!("babs"); "replace attribute")]
+#[test_case(json!({"op": "replace", "path": "title", "value": "Tour Guide"}), "/title", json!("Tour Guide"); "replace missing attribute")]
+#[test_case(json!({"op": "replace", "path": "name", "value": {"givenName": "Babs"}}), "/name", json!({"givenName": "Babs", "familyName": "Jensen"}); "replace complex attribute")]
+#[test_case(json!({"op": "replace", "path": "emails", "value": [{"value": "babs@example.org"}]}), "/emails", json!([{"value": "babs@example.org"}]); "replace multi valued attribute")]
+#[test_case(json!({"op": "replace", "path": "emails[type eq \"work\"]", "value": {"value": "babs@example.org", "type": "work"}}), "/emails/0", json!({"value": "babs@example.org", "type": "work"}); "replace filtered values")]
+#[test_case(json!({"op": "replace", "path": "emails[type eq \"home\"].value", "value": "babs@example.org"}), "/emails/1/value", json!("babs@example.org"); "replace sub attribute of filtered values")]
+#[test_case(json!({"op": "replace", "path": "emails[value ew \"example.com\" or type eq \"home\"].primary", "value": false}), "/emails", json!([{"value": "bjensen@example.com", "type": "work", "primary": false}, {"value": "babs@jensen.org", "type": "home", "primary": false}]); "replace every filtered value")]
+#[test_case(json!({"op": "remove", "path": "active"}), "/active", JsonValue::Null; "remove attribute")]
+#[test_case(json!({"op": "remove", "path": "nickName"}), "/userName", json!("bjensen"); "remove missing attribute")]
+#[test_case(json!({"op": "remove", "path": "name.givenName"}), "/name", json!({"familyName": "Jensen"}); "remove sub attribute")]
+#[test_case(json!({"op": "remove", "path": "emails"}), "/emails", JsonValue::Null; "remove multi valued attribute")]
+#[test_case(json!({"op": "remove", "path": "emails.primary"}), "/emails/0", json!({"value": "bjensen@example.com", "type": "work"}); "remove sub attribute of every value")]
+#[test_case(json!({"op": "remove", "path": "emails[type eq \"work\"]"}), "/emails", json!([{"value": "babs@jensen.org", "type": "home"}]); "remove filtered values")]
+#[test_case(json!({"op": "remove", "path": "emails[type pr]"}), "/emails", JsonValue::Null; "remove every value")]
+#[test_case(json!({"op": "remove", "path": "emails[type eq \"work\"].primary"}), "/emails/0", json!({"value": "bjensen@example.com", "type": "work"}); "remove sub attribute of filtered values")]
+fn apply_operation(operation: JsonValue, pointer: &str, expected: JsonValue) {
+    let user = patch(operation).unwrap();
+    assert_eq!(&expected, user.pointer(pointer).unwrap_or(&JsonValue::Null));
+}
+
+#[test_case(json!({"op": "remove"}), PatchError::MissingPath; "remove without path")]
+#[test_case(json!({"op": "add", "path": "nickName"}), PatchError::MissingValue(PatchOperation::Add); "add without value")]
+#[test_case(json!({"op": "replace", "value": "Babs"}), PatchError::InvalidValue("\"Babs\" is not an object with the attributes of the resource".to_string()); "replace without path with simple value")]
+#[test_case(json!({"op": "replace", "path": "emails[type eq \"fax\"]", "value": {}}), PatchError::NoTarget("emails[type eq \"fax\"]".to_string()); "filter without match")]
+#[test_case(json!({"op": "remove", "path": "phoneNumbers[type eq \"work\"].value"}), PatchError::NoTarget("phoneNumbers[type eq \"work\"].value".to_string()); "filter on missing attribute")]
+#[test_case(json!({"op": "add", "path": "userName.value", "value": "babs"}), PatchError::InvalidPath("userName.value".to_string(), "the attribute has no sub-attributes".to_string()); "sub attribute of simple attribute")]
+#[test_case(json!({"op": "add", "path": "emails[type eq \"work\"]", "value": "babs@example.org"}), PatchError::InvalidValue("\"babs@example.org\" can't be added to the values selected by emails[type eq \"work\"]".to_string()); "add simple value to filtered values")]
+fn invalid_operation(operation: JsonValue, expected: PatchError) {
+    match patch(operation) {
+        Err(Error::Patch(error)) => assert_eq!(expected, error),
+        result => panic!("unexpected result {result:?}"),
+    }
+}
+
+#[test]
+fn failed_operation_leaves_resource_unchanged() {
+    let mut user = user();
+    let operation = PatchOp::add(Some("emails[type pr]"), json!("babs@example.org")).unwrap();
+    assert!(operation.apply(&mut user, &Default::default()).is_err());
+    assert_eq!(self::user(), user);
+}
+
+#[test_case("move", Some("userName"); "unknown operation")]
+#[test_case("add", Some("emails[type eq"); "invalid path")]
+fn invalid_operation_json(op: &str, path: Option<&str>) {
+    let operation = json!({"op": op, "path": path, "value": "babs"});
+    assert!(serde_json::from_value::<PatchOp>(operation).is_err());
+}
+
+#[test]
+fn invalid_path_is_reported_with_the_path() {
+    let Err(Error::Patch(PatchError::InvalidPath(path, _))) =
+        PatchOp::replace(Some("emails[type eq]"), json!("babs"))
+    else {
+        panic!("the path should not be parsed");
+    };
+    assert_eq!("emails[type eq]", path);
+}
+
+#[test]
+fn operation_json_round_trip() {
+    let json =
+        json!({"op": "replace", "path": "members[value eq \"2819c223\"].display", "value": "Babs"});
+    let operation: PatchOp = serde_json::from_value(json.clone()).unwrap();
+    assert_eq!(json, serde_json::to_value(&operation).unwrap());
+}
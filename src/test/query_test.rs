@@ -0,0 +1,58 @@
+use serde_json::{json, Value as JsonValue};
+use test_case::test_case;
+
+use crate::{CompiledFilter, ListResponse, Query, Sort, SortOrder};
+
+fn users() -> Vec<JsonValue> {
+    ["jsmith", "bjensen", "adoe", "mpepperidge", "cblack"]
+        .into_iter()
+        .enumerate()
+        .map(|(index, user_name)| json!({"userName": user_name, "active": index % 2 == 0}))
+        .collect()
+}
+
+fn user_names(response: &ListResponse<JsonValue>) -> Vec<&str> {
+    response
+        .resources
+        .iter()
+        .map(|user| user["userName"].as_str().unwrap())
+        .collect()
+}
+
+#[test_case(Query::new(), 5, 1, vec!["jsmith", "bjensen", "adoe", "mpepperidge", "cblack"]; "all the resources")]
+#[test_case(Query::new().with_start_index(2).with_count(2), 5, 2, vec!["bjensen", "adoe"]; "page")]
+#[test_case(Query::new().with_start_index(4).with_count(10), 5, 4, vec!["mpepperidge", "cblack"]; "last page")]
+#[test_case(Query::new().with_start_index(6), 5, 6, vec![]; "start index past the end")]
+#[test_case(Query::new().with_start_index(0).with_count(1), 5, 1, vec!["jsmith"]; "start index lower than 1")]
+#[test_case(Query::new().with_start_index(-3).with_count(1), 5, 1, vec!["jsmith"]; "negative start index")]
+#[test_case(Query::new().with_count(0), 5, 1, vec![]; "zero count")]
+#[test_case(Query::new().with_count(-1), 5, 1, vec![]; "negative count")]
+#[test_case(Query::new().with_sort(Sort::new("userName").unwrap()).with_count(3), 5, 1, vec!["adoe", "bjensen", "cblack"]; "sorted page")]
+#[test_case(Query::new().with_filter(CompiledFilter::new("active eq true").unwrap()), 3, 1, vec!["jsmith", "adoe", "cblack"]; "filtered")]
+#[test_case(Query::new().with_filter(CompiledFilter::new("active eq true").unwrap()).with_sort(Sort::new("userName").unwrap().with_order(SortOrder::Descending)).with_start_index(2), 3, 2, vec!["cblack", "adoe"]; "filtered and sorted page")]
+#[test_case(Query::new().with_filter(CompiledFilter::new("userName eq \"nobody\"").unwrap()), 0, 1, vec![]; "no match")]
+fn execute_query(query: Query, total_results: usize, start_index: usize, expected: Vec<&str>) {
+    let response = query.execute(users()).unwrap();
+    assert_eq!(total_results, response.total_results);
+    assert_eq!(expected.len(), response.items_per_page);
+    assert_eq!(start_index, response.start_index);
+    assert_eq!(expected, user_names(&response));
+}
+
+#[test]
+fn execute_query_on_resources() {
+    let users = users();
+    let query = Query::new()
+        .with_filter(CompiledFilter::new("userName co \"s\"").unwrap())
+        .with_sort(Sort::new("userName").unwrap())
+        .with_count(1);
+    let response = query.execute_resources(users.iter()).unwrap();
+    assert_eq!(2, response.total_results);
+    assert_eq!(vec![&users[1]], response.resources);
+}
+
+#[test]
+fn query_error() {
+    let query = Query::new().with_filter(CompiledFilter::new("active gt 1").unwrap());
+    assert!(query.execute(users()).is_err());
+}
@@ -0,0 +1,147 @@
+use std::borrow::Cow;
+
+use chrono::{TimeZone, Utc};
+use rust_decimal_macros::dec;
+use serde_json::json;
+use test_case::test_case;
+
+use crate::{AsAttrValue, AttrValue, CompiledFilter, ScimResource};
+
+struct Group {
+    display_name: String,
+    members: Vec<Member>,
+}
+
+struct Member {
+    value: String,
+    primary: bool,
+}
+
+impl ScimResource for Group {
+    fn get_attr(&self, name: &str) -> AttrValue<'_> {
+        match name.to_lowercase().as_str() {
+            "displayname" => self.display_name.as_attr_value(),
+            "members" => self.members.as_attr_value(),
+            _ => AttrValue::Null,
+        }
+    }
+}
+
+impl ScimResource for Member {
+    fn get_attr(&self, name: &str) -> AttrValue<'_> {
+        match name.to_lowercase().as_str() {
+            "value" => self.value.as_attr_value(),
+            "primary" => self.primary.as_attr_value(),
+            _ => AttrValue::Null,
+        }
+    }
+}
+
+impl AsAttrValue for Member {
+    fn as_attr_value(&self) -> AttrValue<'_> {
+        AttrValue::Complex(self)
+    }
+}
+
+fn group() -> Group {
+    Group {
+        display_name: "Tour Guides".to_string(),
+        members: vec![
+            Member {
+                value: "2819c223".to_string(),
+                primary: true,
+            },
+            Member {
+                value: "902c246b".to_string(),
+                primary: false,
+            },
+        ],
+    }
+}
+
+#[test_case("displayName eq \"Tour Guides\"", true; "simple attribute")]
+#[test_case("DISPLAYNAME sw \"tour\"", true; "string comparison is case insensitive")]
+#[test_case("members.value eq \"902c246b\"", true; "sub attribute of multi-valued attribute")]
+#[test_case("members[value eq \"2819c223\" and primary eq true]", true; "value path")]
+#[test_case("members[value eq \"nope\"]", false; "value path without matches")]
+#[test_case("members[value eq \"902c246b\" and primary eq true]", false; "value path on different members")]
+#[test_case("members pr and not (description pr)", true; "present")]
+fn typed_resource_matches(input: &str, expected: bool) {
+    let filter: CompiledFilter = input.parse().unwrap();
+    assert_eq!(expected, filter.matches_resource(&group()).unwrap());
+}
+
+#[test]
+fn typed_resources_are_filtered() {
+    let filter: CompiledFilter = "members.primary eq true".parse().unwrap();
+    let other_group = Group {
+        display_name: "Empty".to_string(),
+        members: vec![],
+    };
+    let filtered = filter.filter_resources(vec![group(), other_group]).unwrap();
+    assert_eq!(1, filtered.len());
+    assert_eq!("Tour Guides", filtered[0].display_name);
+}
+
+#[test]
+fn json_value_attributes_are_case_insensitive() {
+    let resource = json!({"userName": "bjensen", "name": {"familyName": "Jensen"}});
+    assert_eq!(
+        AttrValue::String(Cow::Borrowed("bjensen")),
+        resource.get_attr("USERNAME")
+    );
+    assert_eq!(
+        AttrValue::String(Cow::Borrowed("Jensen")),
+        resource.get_attr("name").into_sub_attr("familyname")
+    );
+    assert_eq!(AttrValue::Null, resource.get_attr("missing"));
+}
+
+#[test_case(json!(null), AttrValue::Null; "null")]
+#[test_case(json!(true), AttrValue::Bool(true); "bool")]
+#[test_case(json!(-3), AttrValue::Number(dec!(-3)); "integer")]
+#[test_case(json!(u64::MAX), AttrValue::Number(dec!(18446744073709551615)); "unsigned integer")]
+#[test_case(json!(2.5), AttrValue::Number(dec!(2.5)); "float")]
+#[test_case(json!(1e300), AttrValue::String(Cow::Owned("1e+300".to_string())); "float out of range")]
+#[test_case(json!(["a", 1]), AttrValue::Multi(vec![AttrValue::String(Cow::Borrowed("a")), AttrValue::Number(dec!(1))]); "array")]
+fn json_value_as_attr_value(value: serde_json::Value, expected: AttrValue) {
+    assert_eq!(expected, value.as_attr_value());
+}
+
+#[test]
+fn rust_types_as_attr_value() {
+    assert_eq!(AttrValue::Number(dec!(42)), 42u8.as_attr_value());
+    assert_eq!(AttrValue::Null, None::<String>.as_attr_value());
+    assert_eq!(
+        AttrValue::String(Cow::Borrowed("a")),
+        Some("a".to_string()).as_attr_value()
+    );
+    let datetime = Utc.with_ymd_and_hms(2021, 1, 1, 10, 0, 0).unwrap();
+    assert_eq!(
+        AttrValue::DateTime(datetime.fixed_offset()),
+        datetime.as_attr_value()
+    );
+    assert_eq!(
+        AttrValue::Multi(vec![AttrValue::Bool(true), AttrValue::Bool(false)]),
+        vec![true, false].as_attr_value()
+    );
+}
+
+#[test_case("datetime gt \"2020-01-01T10:10:10Z\"", true; "datetime comparison")]
+#[test_case("datetime eq \"2021-01-01T10:00:00Z\"", true; "datetime equality")]
+#[test_case("datetime eq \"test\"", false; "datetime with string")]
+fn typed_datetime_matches(input: &str, expected: bool) {
+    struct Resource(chrono::DateTime<Utc>);
+    impl ScimResource for Resource {
+        fn get_attr(&self, name: &str) -> AttrValue<'_> {
+            match name {
+                "datetime" => self.0.as_attr_value(),
+                _ => AttrValue::Null,
+            }
+        }
+    }
+
+    let filter: CompiledFilter = input.parse().unwrap();
+    let resource = Resource(Utc.with_ymd_and_hms(2021, 1, 1, 10, 0, 0).unwrap());
+    assert_eq!(expected, filter.matches_resource(&resource).unwrap());
+}
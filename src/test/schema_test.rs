@@ -0,0 +1,185 @@
+use serde_json::json;
+use test_case::test_case;
+
+use crate::parser::{scim_filter_parser, AttrExpData, Filter};
+use crate::{AttributeDefinition, AttributeType, CompiledFilter, Error, Schema};
+
+const USER_SCHEMA: &str = r#"{
+    "id": "urn:ietf:params:scim:schemas:core:2.0:User",
+    "name": "User",
+    "description": "User Account",
+    "attributes": [
+        {
+            "name": "userName",
+            "type": "string",
+            "multiValued": false,
+            "required": true,
+            "caseExact": false,
+            "mutability": "readWrite",
+            "returned": "default",
+            "uniqueness": "server"
+        },
+        {"name": "active", "type": "boolean"},
+        {"name": "externalId", "type": "string", "caseExact": true},
+        {"name": "loginCount", "type": "integer"},
+        {
+            "name": "emails",
+            "type": "complex",
+            "multiValued": true,
+            "subAttributes": [
+                {"name": "value", "type": "string"},
+                {"name": "type", "type": "string", "canonicalValues": ["work", "home", "other"]},
+                {"name": "primary", "type": "boolean"}
+            ]
+        },
+        {
+            "name": "meta",
+            "type": "complex",
+            "subAttributes": [
+                {"name": "lastModified", "type": "dateTime"}
+            ]
+        }
+    ]
+}"#;
+
+const ENTERPRISE_USER_SCHEMA: &str = r#"{
+    "id": "urn:ietf:params:scim:schemas:extension:enterprise:2.0:User",
+    "attributes": [{"name": "employeeNumber", "type": "string"}]
+}"#;
+
+fn user_schema() -> Schema {
+    USER_SCHEMA
+        .parse::<Schema>()
+        .unwrap()
+        .with_extension(ENTERPRISE_USER_SCHEMA.parse().unwrap())
+}
+
+fn user() -> serde_json::Value {
+    json!({
+        "userName": "42",
+        "externalId": "2021-01-01T00:00:00Z",
+        "active": true,
+        "loginCount": 7,
+        "emails": [
+            {"value": "2021-01-01T00:00:00Z", "type": "work", "primary": true},
+            {"value": "42", "type": "home"}
+        ],
+        "meta": {"lastModified": "2011-05-13T04:42:34Z"},
+        "urn:ietf:params:scim:schemas:extension:enterprise:2.0:User": {
+            "employeeNumber": "701984"
+        }
+    })
+}
+
+#[test]
+fn schema_from_json() {
+    let schema: Schema = USER_SCHEMA.parse().unwrap();
+    assert_eq!("urn:ietf:params:scim:schemas:core:2.0:User", schema.id);
+    assert_eq!(Some("User".to_string()), schema.name);
+    assert_eq!(
+        AttributeDefinition::new("userName", AttributeType::String).required(),
+        schema.attributes[0]
+    );
+    assert_eq!(
+        vec!["work", "home", "other"],
+        schema.attributes[4].sub_attributes[1].canonical_values
+    );
+}
+
+#[test_case(&format!("{{\"Resources\": [{}, {}]}}", USER_SCHEMA, ENTERPRISE_USER_SCHEMA); "list response")]
+#[test_case(&format!("[{}, {}]", USER_SCHEMA, ENTERPRISE_USER_SCHEMA); "array")]
+fn schema_list_from_json(json: &str) {
+    let schemas = Schema::list_from_json(json).unwrap();
+    assert_eq!(2, schemas.len());
+    assert_eq!(
+        "urn:ietf:params:scim:schemas:extension:enterprise:2.0:User",
+        schemas[1].id
+    );
+}
+
+#[test]
+fn single_schema_list_from_json() {
+    assert_eq!(1, Schema::list_from_json(USER_SCHEMA).unwrap().len());
+}
+
+#[test_case("{\"id\": \"urn:x\", \"attributes\": [{\"name\": \"a\"}]}"; "missing type")]
+#[test_case("{\"id\": \"urn:x\", \"attributes\": [{\"name\": \"a\", \"type\": \"text\"}]}"; "unknown type")]
+#[test_case("{\"id\": "; "malformed json")]
+fn invalid_schema(json: &str) {
+    assert!(matches!(
+        json.parse::<Schema>(),
+        Err(Error::InvalidSchema(_))
+    ));
+}
+
+#[test_case("userName", Some("userName"); "attribute")]
+#[test_case("USERNAME", Some("userName"); "case insensitive attribute")]
+#[test_case("emails.primary", Some("primary"); "sub attribute")]
+#[test_case("urn:ietf:params:scim:schemas:core:2.0:User:userName", Some("userName"); "attribute with schema uri")]
+#[test_case("urn:ietf:params:scim:schemas:extension:enterprise:2.0:User:employeeNumber", Some("employeeNumber"); "extension attribute")]
+#[test_case("employeeNumber", None; "extension attribute without uri")]
+#[test_case("urn:acme:User:userName", None; "unknown schema uri")]
+#[test_case("nickName", None; "unknown attribute")]
+fn attribute_definition(attribute: &str, expected: Option<&str>) {
+    let Ok(Filter::AttrExp(AttrExpData::Present(attr_path))) =
+        scim_filter_parser(&format!("{} pr", attribute))
+    else {
+        panic!("{} should be a valid attribute path", attribute);
+    };
+    assert_eq!(
+        expected,
+        user_schema()
+            .attribute_definition(&attr_path)
+            .map(|definition| definition.name.as_str())
+    );
+}
+
+#[test_case("userName eq \"42\"", true; "numeric string")]
+#[test_case("userName sw \"4\"", true; "numeric string starts with")]
+#[test_case("externalId eq \"2021-01-01T00:00:00Z\"", true; "datetime string")]
+#[test_case("externalId co \"01-01\"", true; "datetime string contains")]
+#[test_case("userName eq 42", false; "string with a number")]
+#[test_case("loginCount gt 5", true; "integer")]
+#[test_case("loginCount eq \"7\"", false; "integer with a string")]
+#[test_case("active eq true", true; "boolean")]
+#[test_case("meta.lastModified gt \"2011-01-01T00:00:00Z\"", true; "datetime")]
+#[test_case("emails[value eq \"42\"]", true; "value path with numeric string")]
+#[test_case("emails[value eq \"2021-01-01T00:00:00Z\" and primary eq true]", true; "value path with datetime string")]
+#[test_case("emails.value eq \"42\"", true; "multi-valued sub attribute")]
+#[test_case("urn:ietf:params:scim:schemas:extension:enterprise:2.0:User:employeeNumber eq \"701984\"", true; "extension attribute")]
+#[test_case("userName eq null", false; "null")]
+#[test_case("userName eq \"42\" and emails[type eq \"WORK\"]", true; "case insensitive string")]
+#[test_case("externalId co \"01-01t\"", false; "case exact string")]
+#[test_case("externalId co \"01-01T\"", true; "case exact string with the same case")]
+fn match_with_schema(filter: &str, expected: bool) {
+    let filter = CompiledFilter::new(filter)
+        .unwrap()
+        .with_schema(user_schema());
+    assert_eq!(expected, filter.matches_value(&user()).unwrap());
+}
+
+#[test_case("userName eq \"42\""; "numeric string")]
+#[test_case("externalId co \"01-01\""; "datetime string")]
+fn numeric_and_datetime_strings_do_not_match_without_schema(filter: &str) {
+    let filter = CompiledFilter::new(filter).unwrap();
+    assert!(!filter.matches_value(&user()).unwrap());
+}
+
+#[test_case("loginCount gt \"7\""; "integer with a string")]
+#[test_case("meta.lastModified gt 5"; "datetime with a number")]
+#[test_case("emails gt \"a\""; "complex attribute")]
+fn ordering_with_wrong_type_is_an_error(filter: &str) {
+    let filter = CompiledFilter::new(filter)
+        .unwrap()
+        .with_schema(user_schema());
+    assert!(filter.matches_value(&user()).is_err());
+}
+
+#[test]
+fn attributes_without_definition_are_guessed() {
+    let filter = CompiledFilter::new("nickName eq \"Babs\" and age gt 40")
+        .unwrap()
+        .with_schema(user_schema());
+    let resource = json!({"nickName": "Babs", "age": 42});
+    assert!(filter.matches_value(&resource).unwrap());
+}
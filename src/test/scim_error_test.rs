@@ -0,0 +1,86 @@
+use serde_json::json;
+use test_case::test_case;
+
+use crate::error::ValidationError;
+use crate::parser::CompareOp;
+use crate::{CompiledFilter, Error, PatchError, PatchOperation, ScimError, ScimType};
+
+#[test_case(CompiledFilter::new("userName eq").unwrap_err(); "parse error")]
+#[test_case(CompiledFilter::new("a eq 1e100").unwrap_err(); "number out of range")]
+#[test_case(Error::Validation(ValidationError::UnknownAttribute("usrName".to_string())); "validation error")]
+#[test_case(Error::InvalidFilter; "invalid filter")]
+#[test_case(Error::wrong_operator(&CompareOp::GreaterThan, true); "wrong operator")]
+#[test_case(Error::MalformedDatetime("yesterday".to_string()); "malformed datetime")]
+#[test_case(Error::InvalidComparisonValue("{}".to_string()); "invalid comparison value")]
+fn invalid_filter(error: Error) {
+    let scim_error = error.to_scim_error();
+    assert_eq!(400, scim_error.status);
+    assert_eq!(Some(ScimType::InvalidFilter), scim_error.scim_type);
+    assert_eq!(error.to_string(), scim_error.detail);
+}
+
+#[test_case(PatchError::InvalidOperation("move".to_string()), ScimType::InvalidSyntax; "invalid operation")]
+#[test_case(PatchError::InvalidPath("emails[".to_string(), "unexpected end".to_string()), ScimType::InvalidPath; "invalid path")]
+#[test_case(PatchError::NoTarget("emails[type eq \"fax\"]".to_string()), ScimType::NoTarget; "no target")]
+#[test_case(PatchError::MissingPath, ScimType::NoTarget; "missing path")]
+#[test_case(PatchError::MissingValue(PatchOperation::Add), ScimType::InvalidValue; "missing value")]
+fn patch_error(error: PatchError, scim_type: ScimType) {
+    let scim_error = Error::Patch(error).to_scim_error();
+    assert_eq!(400, scim_error.status);
+    assert_eq!(Some(scim_type), scim_error.scim_type);
+}
+
+#[test_case(Error::InvalidResource; "invalid resource")]
+#[test_case(Error::invalid_schema("missing field `type`"); "invalid schema")]
+#[test_case(serde_json::from_str::<serde_json::Value>("{").unwrap_err().into(); "serialization error")]
+fn internal_error(error: Error) {
+    let scim_error = ScimError::from(error);
+    assert_eq!(500, scim_error.status);
+    assert_eq!(None, scim_error.scim_type);
+}
+
+#[test]
+fn scim_error_json() {
+    let scim_error = ScimError::bad_request(ScimType::InvalidFilter, "unknown attribute");
+    assert_eq!(
+        json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:Error"],
+            "status": "400",
+            "scimType": "invalidFilter",
+            "detail": "unknown attribute"
+        }),
+        serde_json::to_value(scim_error).unwrap()
+    );
+}
+
+#[test]
+fn scim_error_json_without_scim_type() {
+    assert_eq!(
+        json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:Error"],
+            "status": "500",
+            "detail": "failure"
+        }),
+        serde_json::to_value(ScimError::internal("failure")).unwrap()
+    );
+}
+
+#[test_case(ScimType::InvalidFilter; "invalid filter")]
+#[test_case(ScimType::TooMany; "too many")]
+#[test_case(ScimType::InvalidPath; "invalid path")]
+#[test_case(ScimType::NoTarget; "no target")]
+#[test_case(ScimType::InvalidVers; "invalid vers")]
+fn scim_type_json(scim_type: ScimType) {
+    assert_eq!(
+        json!(scim_type.as_str()),
+        serde_json::to_value(scim_type).unwrap()
+    );
+}
+
+#[test]
+fn scim_error_display() {
+    assert_eq!(
+        "400 tooMany: too many results",
+        ScimError::bad_request(ScimType::TooMany, "too many results").to_string()
+    );
+}
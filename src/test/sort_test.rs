@@ -0,0 +1,149 @@
+use serde_json::{json, Value as JsonValue};
+use test_case::test_case;
+
+use crate::schema::{AttributeDefinition, AttributeType};
+use crate::{scim_filter, Error, Schema, ScimType, Sort, SortOrder};
+
+fn sorted_ids(sort: &Sort, resources: Vec<JsonValue>) -> Vec<String> {
+    sort.sort(resources)
+        .unwrap()
+        .iter()
+        .map(|resource| resource["id"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[test_case("userName", None, vec!["2", "1", "3"]; "strings")]
+#[test_case("userName", Some("descending"), vec!["3", "1", "2"]; "strings descending")]
+#[test_case("USERNAME", Some("Ascending"), vec!["2", "1", "3"]; "case insensitive parameters")]
+#[test_case("age", None, vec!["1", "3", "2"]; "numbers")]
+#[test_case("meta.created", None, vec!["3", "1", "2"]; "datetimes with different offsets")]
+#[test_case("active", None, vec!["2", "1", "3"]; "booleans")]
+#[test_case("nickName", None, vec!["1", "3", "2"]; "missing values last")]
+#[test_case("nickName", Some("descending"), vec!["3", "1", "2"]; "missing values last when descending")]
+#[test_case("emails", None, vec!["3", "1", "2"]; "primary value of multi valued attribute")]
+#[test_case("emails.value", None, vec!["3", "1", "2"]; "sub attribute of primary value")]
+#[test_case("roles", None, vec!["2", "1", "3"]; "first value without primary")]
+#[test_case("urn:ietf:params:scim:schemas:core:2.0:User:userName", None, vec!["2", "1", "3"]; "core schema uri")]
+#[test_case("urn:ietf:params:scim:schemas:extension:enterprise:2.0:User:department", None, vec!["2", "1", "3"]; "extension schema uri")]
+fn sort_users(sort_by: &str, sort_order: Option<&str>, expected: Vec<&str>) {
+    let users = vec![
+        json!({
+            "id": "1",
+            "userName": "bjensen",
+            "nickName": "Babs",
+            "age": 9,
+            "active": true,
+            "meta": {"created": "2011-08-01T21:32:44+02:00"},
+            "emails": [
+                {"value": "z@example.com"},
+                {"value": "babs@example.com", "primary": true}
+            ],
+            "roles": ["developer", "admin"],
+            "urn:ietf:params:scim:schemas:extension:enterprise:2.0:User": {"department": "Sales"}
+        }),
+        json!({
+            "id": "2",
+            "userName": "Alice",
+            "age": 10,
+            "active": false,
+            "meta": {"created": "2011-08-01T20:32:44+00:00"},
+            "roles": ["admin"],
+            "urn:ietf:params:scim:schemas:extension:enterprise:2.0:User": {"department": "Marketing"}
+        }),
+        json!({
+            "id": "3",
+            "userName": "jsmith",
+            "nickName": "Jay",
+            "age": 9.5,
+            "meta": {"created": "2011-08-01T19:00:00Z"},
+            "emails": [{"value": "a@example.com", "primary": true}],
+            "roles": ["tester"],
+            "urn:ietf:params:scim:schemas:extension:enterprise:2.0:User": {"department": "Tour"}
+        }),
+    ];
+    let sort = Sort::from_params(sort_by, sort_order).unwrap();
+    assert_eq!(expected, sorted_ids(&sort, users));
+}
+
+#[test]
+fn sort_with_schema() {
+    let schema = Schema::new(
+        Schema::USER_URI,
+        vec![
+            AttributeDefinition::new("id", AttributeType::String).case_exact(),
+            AttributeDefinition::new("employeeNumber", AttributeType::Integer),
+        ],
+    );
+    let users = vec![
+        json!({"id": "b", "employeeNumber": "10"}),
+        json!({"id": "B", "employeeNumber": "9"}),
+        json!({"id": "a", "employeeNumber": "100"}),
+    ];
+
+    let sort = Sort::new("id").unwrap().with_schema(schema.clone());
+    assert_eq!(vec!["B", "a", "b"], sorted_ids(&sort, users.clone()));
+    let sort = Sort::new("employeeNumber").unwrap().with_schema(schema);
+    assert_eq!(vec!["B", "b", "a"], sorted_ids(&sort, users));
+}
+
+#[test]
+fn numbers_in_strings_without_schema() {
+    let users = vec![
+        json!({"id": "a", "employeeNumber": 5}),
+        json!({"id": "b", "employeeNumber": "10"}),
+        json!({"id": "c", "employeeNumber": "9"}),
+    ];
+    let sort = Sort::new("employeeNumber").unwrap();
+    assert_eq!(vec!["a", "c", "b"], sorted_ids(&sort, users.clone()));
+    // a string attribute in the schema keeps them strings, sorted after the number
+    let schema = Schema::new(
+        Schema::USER_URI,
+        vec![AttributeDefinition::new(
+            "employeeNumber",
+            AttributeType::String,
+        )],
+    );
+    let sort = sort.with_schema(schema);
+    assert_eq!(vec!["a", "b", "c"], sorted_ids(&sort, users));
+}
+
+#[test]
+fn equal_values_keep_their_order() {
+    let users = vec![
+        json!({"id": "1", "active": true}),
+        json!({"id": "2", "active": false}),
+        json!({"id": "3", "active": true}),
+        json!({"id": "4"}),
+        json!({"id": "5", "active": false}),
+    ];
+    let sort = Sort::new("active")
+        .unwrap()
+        .with_order(SortOrder::Descending);
+    assert_eq!(vec!["1", "3", "2", "5", "4"], sorted_ids(&sort, users));
+}
+
+#[test]
+fn sort_resources_in_place() {
+    let users = [
+        json!({"userName": "jsmith"}),
+        json!({"userName": "bjensen"}),
+    ];
+    let mut filtered = scim_filter("userName pr", users.iter()).unwrap();
+    Sort::new("userName").unwrap().sort_resources(&mut filtered);
+    assert_eq!(vec![&users[1], &users[0]], filtered);
+}
+
+#[test_case("name.", None; "invalid sort by")]
+#[test_case("userName eq \"bjensen\"", None; "filter as sort by")]
+#[test_case("userName", Some("up"); "invalid sort order")]
+fn invalid_sort(sort_by: &str, sort_order: Option<&str>) {
+    let error = Sort::from_params(sort_by, sort_order).unwrap_err();
+    assert!(matches!(
+        error,
+        Error::InvalidSortBy(_, _) | Error::InvalidSortOrder(_)
+    ));
+    assert_eq!(
+        Some(ScimType::InvalidValue),
+        error.to_scim_error().scim_type
+    );
+}
@@ -0,0 +1,118 @@
+use test_case::test_case;
+
+use crate::error::ValidationError;
+use crate::parser::{
+    attr_path_parser, scim_filter_parser, CompareOp, Filter, LogExpData, LogExpOperator, ValFilter,
+    ValuePathData,
+};
+use crate::{AttributeType, Error, Schema};
+
+fn validate(filter: &str) -> Result<(), ValidationError> {
+    let schema = Schema::user().with_extension(Schema::enterprise_user());
+    scim_filter_parser(filter).unwrap().validate(&schema)
+}
+
+#[test_case("userName eq \"bjensen\""; "string attribute")]
+#[test_case("USERNAME sw \"b\""; "case insensitive attribute")]
+#[test_case("name.familyName co \"jen\""; "sub attribute")]
+#[test_case("active eq true"; "boolean")]
+#[test_case("meta.lastModified gt \"2011-05-13T04:42:34Z\""; "datetime")]
+#[test_case("emails[type eq \"work\" and (primary eq true or value ew \".com\")]"; "value path")]
+#[test_case("title pr and not (nickName eq null)"; "present and null")]
+#[test_case("urn:ietf:params:scim:schemas:core:2.0:User:userName pr"; "core schema uri")]
+#[test_case("urn:ietf:params:scim:schemas:extension:enterprise:2.0:User:manager.value eq \"26118915\""; "extension schema uri")]
+fn valid_filter(filter: &str) {
+    assert_eq!(Ok(()), validate(filter));
+}
+
+#[test_case("usrName eq \"x\"", "usrName"; "misspelled attribute")]
+#[test_case("name.famlyName pr", "name.famlyName"; "misspelled sub attribute")]
+#[test_case("userName pr and emails[tpe eq \"work\"]", "tpe"; "value filter attribute")]
+#[test_case("employeeNumber pr", "employeeNumber"; "extension attribute without uri")]
+#[test_case("urn:acme:User:userName pr", "urn:acme:User:userName"; "unknown schema uri")]
+fn unknown_attribute(filter: &str, attribute: &str) {
+    assert_eq!(
+        Err(ValidationError::UnknownAttribute(attribute.to_string())),
+        validate(filter)
+    );
+}
+
+#[test_case("userName.value pr", "userName.value"; "sub attribute")]
+#[test_case("active[value eq true]", "active"; "value path")]
+fn sub_attribute_of_simple_attribute(filter: &str, attribute: &str) {
+    assert_eq!(
+        Err(ValidationError::SubAttributeOfSimpleAttribute(
+            attribute.to_string()
+        )),
+        validate(filter)
+    );
+}
+
+#[test_case("active gt true", CompareOp::GreaterThan, "active", AttributeType::Boolean; "ordering on boolean")]
+#[test_case("meta.created co \"2011\"", CompareOp::Contains, "meta.created", AttributeType::DateTime; "contains on datetime")]
+#[test_case("x509Certificates.value lt \"MII\"", CompareOp::LessThan, "x509Certificates.value", AttributeType::Binary; "ordering on binary")]
+#[test_case("name eq \"Babs\"", CompareOp::Equal, "name", AttributeType::Complex; "comparison on complex")]
+#[test_case("title gt null", CompareOp::GreaterThan, "title", AttributeType::String; "ordering with null")]
+fn unsupported_operator(
+    filter: &str,
+    operator: CompareOp,
+    attribute: &str,
+    attr_type: AttributeType,
+) {
+    assert_eq!(
+        Err(ValidationError::UnsupportedOperator {
+            operator,
+            attribute: attribute.to_string(),
+            attr_type
+        }),
+        validate(filter)
+    );
+}
+
+#[test]
+fn value_path_built_inside_a_value_filter() {
+    // the grammar doesn't allow it, but the public constructors can build it
+    let value_path = |left: &str, right: &str| {
+        let val_filter = ValFilter::log_exp(LogExpData::new((
+            scim_filter_parser(left).unwrap(),
+            LogExpOperator::And,
+            scim_filter_parser(right).unwrap(),
+        )));
+        Filter::ValuePath(ValuePathData::new((
+            attr_path_parser("emails").unwrap(),
+            val_filter,
+        )))
+        .into_owned()
+    };
+    let schema = Schema::user();
+    assert_eq!(
+        Err(ValidationError::SubAttributeOfSimpleAttribute(
+            "type".to_string()
+        )),
+        value_path("type eq \"work\"", "type[value eq \"x\"]").validate(&schema)
+    );
+    assert_eq!(
+        Err(ValidationError::UnknownAttribute("tpe".to_string())),
+        value_path("primary eq true", "tpe[value eq \"x\"]").validate(&schema)
+    );
+}
+
+#[test_case("active eq \"true\"", "\"true\"", AttributeType::Boolean; "string on boolean")]
+#[test_case("userName eq 42", "42", AttributeType::String; "number on string")]
+#[test_case("meta.lastModified gt \"yesterday\"", "\"yesterday\"", AttributeType::DateTime; "malformed datetime")]
+#[test_case("emails[primary eq 1]", "1", AttributeType::Boolean; "number on boolean in value path")]
+fn wrong_value_type(filter: &str, value: &str, attr_type: AttributeType) {
+    assert!(matches!(
+        validate(filter),
+        Err(ValidationError::WrongValueType { value: v, attr_type: t, .. }) if v == value && t == attr_type
+    ));
+}
+
+#[test]
+fn validation_error_message() {
+    let error = Error::from(validate("active gt true").unwrap_err());
+    assert_eq!(
+        "the operator gt (greater than) can't be applied to the attribute active of type boolean",
+        error.to_string()
+    );
+}
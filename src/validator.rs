@@ -0,0 +1,190 @@
+use chrono::DateTime;
+
+use crate::error::ValidationError;
+use crate::parser::{
+    AttrExpData, AttrPath, CompValue, CompareOp, Filter, LogExpData, ValFilter, ValuePathData,
+};
+use crate::schema::{
+    find_attribute_definition, find_by_name, AttributeDefinition, AttributeType, Schema,
+};
+
+#[cfg(test)]
+#[path = "test/validator_test.rs"]
+mod validator_test;
+
+type ValidationResult = Result<(), ValidationError>;
+
+impl<'a> Filter<'a> {
+    /// checks that the filter only uses attributes defined by the schema, with operators and values
+    /// that fit their types, without touching any resource.
+    ///
+    /// ```
+    /// use scim_filter::parser::scim_filter_parser;
+    /// use scim_filter::{Schema, ValidationError};
+    ///
+    /// let filter = scim_filter_parser("usrName eq \"bjensen\"").unwrap();
+    /// assert_eq!(
+    ///     Err(ValidationError::UnknownAttribute("usrName".to_string())),
+    ///     filter.validate(&Schema::user())
+    /// );
+    /// ```
+    pub fn validate(&self, schema: &Schema) -> ValidationResult {
+        let attributes = |attr_path: &AttrPath| {
+            schema
+                .attributes_of(attr_path.uri().as_deref())
+                .ok_or_else(|| ValidationError::UnknownAttribute(attr_path.to_string()))
+        };
+        match self {
+            Filter::AttrExp(attr_exp_data) => {
+                attr_exp_data.validate(attributes(attr_exp_data.attr_path())?)
+            }
+            Filter::LogExp(log_exp_data) => log_exp_data.validate(schema),
+            Filter::ValuePath(value_path_data) => {
+                value_path_data.validate(attributes(value_path_data.attr_path())?)
+            }
+            Filter::Sub(_, filter) => filter.validate(schema),
+        }
+    }
+}
+
+impl<'a> LogExpData<'a> {
+    pub fn validate(&self, schema: &Schema) -> ValidationResult {
+        self.left.validate(schema)?;
+        self.right.validate(schema)
+    }
+}
+
+impl<'a> ValuePathData<'a> {
+    /// the attribute of a value path must be complex, the value filter is validated against its
+    /// sub-attributes
+    pub fn validate(&self, attributes: &[AttributeDefinition]) -> ValidationResult {
+        let definition = self.attr_path().definition_in(attributes)?;
+        if definition.attr_type != AttributeType::Complex {
+            return Err(ValidationError::SubAttributeOfSimpleAttribute(
+                self.attr_path().to_string(),
+            ));
+        }
+        self.val_filter().validate(&definition.sub_attributes)
+    }
+}
+
+impl<'a> ValFilter<'a> {
+    pub fn validate(&self, attributes: &[AttributeDefinition]) -> ValidationResult {
+        match self {
+            ValFilter::AttrExp(attr_exp_data) => attr_exp_data.validate(attributes),
+            ValFilter::LogExp(log_exp_data) => {
+                Self::validate_operand(&log_exp_data.left, attributes)?;
+                Self::validate_operand(&log_exp_data.right, attributes)
+            }
+            ValFilter::SubFilter(_, val_filter) => val_filter.validate(attributes),
+        }
+    }
+
+    /// the sides of a logical expression in a value filter can only be attribute expressions,
+    /// logical expressions and parenthesised filters
+    fn validate_operand(operand: &Filter, attributes: &[AttributeDefinition]) -> ValidationResult {
+        match operand {
+            Filter::AttrExp(attr_exp_data) => attr_exp_data.validate(attributes),
+            Filter::LogExp(log_exp_data) => {
+                Self::validate_operand(&log_exp_data.left, attributes)?;
+                Self::validate_operand(&log_exp_data.right, attributes)
+            }
+            Filter::ValuePath(value_path_data) => value_path_data.validate(attributes),
+            Filter::Sub(_, filter) => Self::validate_operand(filter, attributes),
+        }
+    }
+}
+
+impl<'a> AttrExpData<'a> {
+    pub fn validate(&self, attributes: &[AttributeDefinition]) -> ValidationResult {
+        let definition = self.attr_path().definition_in(attributes)?;
+        match self {
+            AttrExpData::Present(_) => Ok(()),
+            AttrExpData::Compare(attr_path, compare_op, comp_value) => {
+                comp_value.validate(compare_op, attr_path, definition.attr_type)
+            }
+        }
+    }
+}
+
+impl AttrPath {
+    /// finds the definition of the attribute among the given ones, ignoring the schema URI
+    fn definition_in<'s>(
+        &self,
+        attributes: &'s [AttributeDefinition],
+    ) -> Result<&'s AttributeDefinition, ValidationError> {
+        if let Some(definition) = find_attribute_definition(attributes, self) {
+            return Ok(definition);
+        }
+        match find_by_name(attributes, self.attr_name()) {
+            Some(definition)
+                if self.sub_attr().is_some() && definition.attr_type != AttributeType::Complex =>
+            {
+                Err(ValidationError::SubAttributeOfSimpleAttribute(
+                    self.to_string(),
+                ))
+            }
+            _ => Err(ValidationError::UnknownAttribute(self.to_string())),
+        }
+    }
+}
+
+impl<'a> CompValue<'a> {
+    /// checks that the operator and the value fit the type of the attribute
+    pub fn validate(
+        &self,
+        compare_op: &CompareOp,
+        attr_path: &AttrPath,
+        attr_type: AttributeType,
+    ) -> ValidationResult {
+        let is_equality = matches!(compare_op, CompareOp::Equal | CompareOp::NotEqual);
+        let is_ordering = matches!(
+            compare_op,
+            CompareOp::GreaterThan
+                | CompareOp::GreaterThanOrEqual
+                | CompareOp::LessThan
+                | CompareOp::LessThanOrEqual
+        );
+        let supported_operator = match (self, attr_type) {
+            // any attribute can be compared with null, to check if it has a value
+            (CompValue::Null, _) => is_equality,
+            (_, AttributeType::Complex) => false,
+            (_, AttributeType::Boolean) => is_equality,
+            (_, AttributeType::Binary) => !is_ordering,
+            (_, AttributeType::Decimal | AttributeType::Integer | AttributeType::DateTime) => {
+                is_equality || is_ordering
+            }
+            (_, AttributeType::String | AttributeType::Reference) => true,
+        };
+        if !supported_operator {
+            return Err(ValidationError::UnsupportedOperator {
+                operator: *compare_op,
+                attribute: attr_path.to_string(),
+                attr_type,
+            });
+        }
+
+        let valid_value = match (self, attr_type) {
+            (CompValue::Null, _) => true,
+            (CompValue::True | CompValue::False, AttributeType::Boolean) => true,
+            (CompValue::Number(_), AttributeType::Decimal) => true,
+            (CompValue::Number(number), AttributeType::Integer) => number.fract().is_zero(),
+            (CompValue::String(string), AttributeType::DateTime) => {
+                DateTime::parse_from_rfc3339(string).is_ok()
+            }
+            (
+                CompValue::String(_),
+                AttributeType::String | AttributeType::Binary | AttributeType::Reference,
+            ) => true,
+            _ => false,
+        };
+        if !valid_value {
+            return Err(ValidationError::WrongValueType {
+                value: self.to_string(),
+                attribute: attr_path.to_string(),
+                attr_type,
+            });
+        }
+        Ok(())
+    }
+}